@@ -4,72 +4,798 @@ use rvemu::csr;
 use rvemu::devices::dram::Dram;
 use rvemu::exception::Exception;
 use std::fs::File;
-use std::io::Read;
+use std::io;
+use std::io::{Read, Write};
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 
-use crate::spf::SysProvided;
+use crate::celestial::{table_bytes, Celestial, CelestialError};
+use crate::cost::CostTable;
+use crate::devices::{Devices, MMIO_USED};
+use crate::elf::{ElfError, ElfImage};
+use crate::memory::{read_bytes, write_bytes, GuestMemory, MemoryMap, Rom, CELESTIALS_OFFSET};
+use crate::spf::{SpfFault, SpfState, SysProvided, SPF_END_ADDR, SPF_MIN_ADDR};
 
-const DRAM_BASE: u64 = 0x40000000;
-const DRAM_SIZE: u64 = 0x2000;
+#[derive(Debug)]
+pub enum NvramError {
+    Io(io::Error),
+    /// A saved image is bigger than this machine's NVRAM.
+    TooLarge {
+        len: usize,
+        capacity: usize,
+    },
+    Memory(Exception),
+}
+
+impl From<io::Error> for NvramError {
+    fn from(e: io::Error) -> Self {
+        NvramError::Io(e)
+    }
+}
 
-#[allow(dead_code)]
-const MMIO_BASE: u64 = 0x90000000;
-#[allow(dead_code)]
-const MMIO_SIZE: u64 = 0x100000;
+impl From<Exception> for NvramError {
+    fn from(e: Exception) -> Self {
+        NvramError::Memory(e)
+    }
+}
 
-const ROM_BASE: u64 = 0x80000000;
-const ROM_SIZE: u64 = 0x100000;
+/// Syscall numbers for `ecall`, selected by `a7`. Arguments are passed in
+/// `a0` onwards, the same as a function call.
+///
+/// Ends the tick; `a0` is the exit code. `_end` in `start.s` uses this so
+/// returning from `main` is a clean exit.
+pub const SYS_EXIT: u64 = 0;
+/// Gives up the rest of this tick's budget.
+pub const SYS_YIELD: u64 = 1;
+/// Stops the program because it found itself in a bad state; `a0` is a
+/// program defined reason.
+pub const SYS_ABORT: u64 = 2;
 
-const NVRAM_BASE: u64 = 0xa0000400;
-const NVRAM_SIZE: u64 = 0x100000;
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// The cpu raised an exception that wasn't an `ecall`. Loads, stores
+    /// and jumps outside the `MemoryMap` end up here as access faults.
+    Cpu(Exception),
+    /// A system provided function was called with arguments it couldn't
+    /// use.
+    Spf(SpfFault),
+}
+
+/// How a call to `execute_budget` came to an end.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionOutcome {
+    /// Every instruction in the budget was retired and the program is
+    /// still running. `retired` can be over the budget when a system
+    /// provided function crossed the end of it.
+    BudgetExhausted {
+        retired: u64,
+    },
+    /// The program called `SYS_EXIT`, normally by returning from `main`.
+    Exited {
+        retired: u64,
+        code: i64,
+    },
+    /// The program called `SYS_YIELD`.
+    Yielded {
+        retired: u64,
+    },
+    /// The program called `SYS_ABORT`.
+    Aborted {
+        retired: u64,
+        code: i64,
+    },
+    /// The program made an `ecall` with an `a7` we don't know.
+    BadSyscall {
+        retired: u64,
+        number: u64,
+    },
+    Faulted {
+        retired: u64,
+        fault: Fault,
+    },
+}
 
 pub struct BubblyByter {
     cpu: Cpu,
+    memory_map: MemoryMap,
     dram: Arc<Mutex<Dram>>,
-    #[allow(dead_code)]
+    rom: Arc<Mutex<Rom>>,
     nvram: Arc<Mutex<Dram>>,
+    /// Mounted in MMIO.
+    devices: Arc<Mutex<Devices>>,
+    spf: Arc<Mutex<SpfState>>,
+    /// The loaded program, copied back into DRAM on every `reset`.
+    firmware: Vec<u8>,
+    entry: u64,
+    /// What the last `execute_budget` went over its budget by, taken off
+    /// the next one. It isn't cleared by `reset`, which runs every tick.
+    overdraft: u64,
+}
+
+impl Default for BubblyByter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BubblyByter {
     pub fn new() -> BubblyByter {
-        let jh = SysProvided {};
+        let memory_map = MemoryMap::default();
+        assert!(
+            MMIO_USED <= memory_map.mmio.size,
+            "the devices don't fit in MMIO"
+        );
+
         let mut cpu = Cpu::new();
-        cpu.with_jump_link_handler(Box::new(jh));
+        let mut memory = GuestMemory::new();
+
+        let dram = Arc::new(Mutex::new(Dram::new(memory_map.ram.size)));
+        cpu.bus.mount(memory_map.ram.base, dram.clone());
+        memory.mount(memory_map.ram, dram.clone());
 
-        let dram = Arc::new(Mutex::new(Dram::new(DRAM_SIZE)));
-        cpu.bus.mount(DRAM_BASE, dram.clone());
+        let rom = Arc::new(Mutex::new(Rom::new(memory_map.rom.size)));
+        cpu.bus.mount(memory_map.rom.base, rom.clone());
+        memory.mount_read_only(memory_map.rom, rom.clone());
 
-        let rom = Arc::new(Mutex::new(Dram::new(ROM_SIZE)));
-        cpu.bus.mount(ROM_BASE, rom.clone());
+        let devices = Arc::new(Mutex::new(Devices::new()));
+        cpu.bus.mount(memory_map.mmio.base, devices.clone());
+        memory.mount_read_only(memory_map.mmio, devices.clone());
 
-        let nvram = Arc::new(Mutex::new(Dram::new(NVRAM_SIZE)));
-        cpu.bus.mount(NVRAM_BASE, nvram.clone());
+        let nvram = Arc::new(Mutex::new(Dram::new(memory_map.nvram.size)));
+        cpu.bus.mount(memory_map.nvram.base, nvram.clone());
+        memory.mount(memory_map.nvram, nvram.clone());
 
-        let mut b = BubblyByter { cpu, dram, nvram };
+        let spf = Arc::new(Mutex::new(SpfState::default()));
+        let jh = SysProvided::new(memory, spf.clone());
+        cpu.with_jump_link_handler(Box::new(jh));
+
+        let entry = memory_map.ram.base;
+        let mut b = BubblyByter {
+            cpu,
+            memory_map,
+            dram,
+            rom,
+            nvram,
+            devices,
+            spf,
+            firmware: vec![],
+            entry,
+            overdraft: 0,
+        };
         b.reset();
         b
     }
 
+    /// Puts the machine back into its power-on state: registers are
+    /// cleared and DRAM is wiped and reloaded with the firmware. NVRAM is
+    /// left alone so programs can carry state from one tick to the next.
     pub fn reset(&mut self) {
+        let mut dram = self.dram.lock().unwrap();
+        dram.initialize(vec![0; self.memory_map.ram.size as usize]);
+        dram.initialize(self.firmware.clone());
+        drop(dram);
+
+        let mut spf = self.spf.lock().unwrap();
+        spf.fault = None;
+        spf.charge = None;
+        drop(spf);
+
         self.cpu.reset();
-        self.cpu.pc = DRAM_BASE;
+        self.cpu.pc = self.entry;
         self.cpu
             .xregs
-            .write(cpu::REG_SP, DRAM_BASE + DRAM_SIZE - 0x400);
+            .write(cpu::REG_SP, self.memory_map.stack_top());
     }
 
-    pub fn load_kernel<P: AsRef<Path>>(&self, kernel_img: P) {
+    pub fn memory_map(&self) -> &MemoryMap {
+        &self.memory_map
+    }
+
+    /// Loads a flat image to the start of RAM and starts executing it
+    /// from its first byte.
+    pub fn load_kernel<P: AsRef<Path>>(&mut self, kernel_img: P) -> io::Result<()> {
         let mut prog = vec![];
-        let mut f = File::open(kernel_img).unwrap();
-        f.read_to_end(&mut prog).unwrap();
-        self.dram.lock().unwrap().initialize(prog);
+        let mut f = File::open(kernel_img)?;
+        f.read_to_end(&mut prog)?;
+        self.firmware = prog;
+        self.entry = self.memory_map.ram.base;
+        self.spf.lock().unwrap().link_map.clear();
+        self.reset();
+        Ok(())
     }
 
-    pub fn execute(&mut self, max_cycle: u64) -> Result<(), Exception> {
-        while self.cpu.state.read(csr::TIME) < max_cycle {
-            let (_, (_fetch_pc, _fetch_p_pc, _instr)) = self.cpu.cycle()?;
+    /// Loads a linked `kernel.elf`, placing each segment at its linked
+    /// address and starting at the ELF entry point.
+    ///
+    /// Segments without file contents outside RAM are skipped; those are
+    /// the linker reserving the symbols for ROM, MMIO and NVRAM.
+    pub fn load_elf<P: AsRef<Path>>(&mut self, kernel_elf: P) -> Result<(), ElfError> {
+        let mut bytes = vec![];
+        let mut f = File::open(kernel_elf)?;
+        f.read_to_end(&mut bytes)?;
+        self.load_elf_bytes(&bytes)
+    }
+
+    fn load_elf_bytes(&mut self, bytes: &[u8]) -> Result<(), ElfError> {
+        let elf = ElfImage::parse(bytes)?;
+
+        let ram = self.memory_map.ram;
+        // Everything with contents has to fit in RAM, wherever it's linked.
+        let size = elf
+            .segments
+            .iter()
+            .filter(|seg| !seg.data.is_empty() || ram.contains_range(seg.vaddr, seg.memsz))
+            .fold(0u64, |size, seg| size.saturating_add(seg.memsz));
+        if size > ram.size {
+            return Err(ElfError::TooLarge {
+                size,
+                capacity: ram.size,
+            });
+        }
+
+        let mut in_ram = vec![];
+        for seg in elf.segments {
+            if ram.contains_range(seg.vaddr, seg.memsz) {
+                in_ram.push(seg);
+            } else if !seg.data.is_empty() || !self.memory_map.contains_range(seg.vaddr, seg.memsz)
+            {
+                return Err(ElfError::OutsideRam {
+                    index: seg.index,
+                    vaddr: seg.vaddr,
+                    memsz: seg.memsz,
+                });
+            }
         }
+
+        if !ram.contains(elf.entry) {
+            return Err(ElfError::EntryOutsideRam { entry: elf.entry });
+        }
+
+        let image_end = in_ram
+            .iter()
+            .map(|seg| seg.vaddr + seg.data.len() as u64)
+            .max()
+            .unwrap_or(ram.base);
+        let mut image = vec![0; (image_end - ram.base) as usize];
+        for seg in in_ram {
+            let start = (seg.vaddr - ram.base) as usize;
+            image[start..start + seg.data.len()].copy_from_slice(&seg.data);
+        }
+
+        self.firmware = image;
+        self.entry = elf.entry;
+        self.spf.lock().unwrap().link_map = elf
+            .symbols
+            .into_iter()
+            .filter(|(_, addr)| (SPF_MIN_ADDR..SPF_END_ADDR).contains(addr))
+            .map(|(name, addr)| (addr, name))
+            .collect();
+        self.reset();
+        Ok(())
+    }
+
+    /// Takes everything the program has written with `debuglog` and
+    /// `debuglogf` since the last drain, one string per call. The second
+    /// value is how many messages were dropped because the log was full.
+    pub fn drain_log(&mut self) -> (Vec<String>, usize) {
+        let mut spf = self.spf.lock().unwrap();
+        let log = spf
+            .log
+            .drain(..)
+            .map(|m| String::from_utf8_lossy(&m).into_owned())
+            .collect();
+        spf.log_bytes = 0;
+        (log, std::mem::take(&mut spf.log_dropped))
+    }
+
+    /// Writes the celestial table into ROM, where the guest sees it as
+    /// `celestials`. ROM survives `reset`, so this only needs calling when
+    /// the bodies have moved on, normally once before each tick.
+    pub fn load_celestials(&mut self, bodies: &[Celestial]) -> Result<(), CelestialError> {
+        let table = table_bytes(bodies)?;
+        let mut rom = self.rom.lock().unwrap();
+        write_bytes(&mut rom.dram, CELESTIALS_OFFSET, &table)?;
         Ok(())
     }
+
+    /// Returns a copy of the whole NVRAM.
+    pub fn save_nvram(&self) -> Result<Vec<u8>, NvramError> {
+        let nvram = self.nvram.lock().unwrap();
+        Ok(read_bytes(&*nvram, 0, self.memory_map.nvram.size)?)
+    }
+
+    /// Replaces the NVRAM with `image`. A short image is padded with
+    /// zeros, so an empty one is the same as a factory-fresh NVRAM.
+    pub fn restore_nvram(&mut self, image: &[u8]) -> Result<(), NvramError> {
+        let capacity = self.memory_map.nvram.size as usize;
+        if image.len() > capacity {
+            return Err(NvramError::TooLarge {
+                len: image.len(),
+                capacity,
+            });
+        }
+        let mut padded = image.to_vec();
+        padded.resize(capacity, 0);
+        self.nvram.lock().unwrap().initialize(padded);
+        Ok(())
+    }
+
+    pub fn save_nvram_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), NvramError> {
+        let image = self.save_nvram()?;
+        let mut f = File::create(path)?;
+        f.write_all(&image)?;
+        Ok(())
+    }
+
+    pub fn restore_nvram_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), NvramError> {
+        let mut image = vec![];
+        let mut f = File::open(path)?;
+        f.read_to_end(&mut image)?;
+        self.restore_nvram(&image)
+    }
+
+    /// The ship's devices, as the guest sees them in MMIO. Hold on to the
+    /// guard only between calls to `execute_budget`: the guest's loads and
+    /// stores lock it too.
+    pub fn devices(&self) -> MutexGuard<'_, Devices> {
+        self.devices.lock().unwrap()
+    }
+
+    pub fn devices_mut(&mut self) -> MutexGuard<'_, Devices> {
+        self.devices.lock().unwrap()
+    }
+
+    /// Sets what each system provided function costs against the
+    /// instruction budget.
+    pub fn set_cost_table(&mut self, costs: CostTable) {
+        self.spf.lock().unwrap().costs = costs;
+    }
+
+    /// Runs until `n_instructions` have been charged, the program makes a
+    /// syscall that ends the tick, or the cpu faults. Every instruction,
+    /// the `ecall` included, is charged as one, except a call to a system
+    /// provided function, which is charged its cost from the `CostTable`.
+    /// The call that crosses the end of the budget still completes, so
+    /// `retired` can come out a little over; what it went over by is taken
+    /// off the next call's budget.
+    pub fn execute_budget(&mut self, n_instructions: u64) -> ExecutionOutcome {
+        let repaid = self.overdraft.min(n_instructions);
+        self.overdraft -= repaid;
+        let budget = n_instructions - repaid;
+
+        let mut retired = 0;
+        let outcome = loop {
+            if retired >= budget {
+                break ExecutionOutcome::BudgetExhausted { retired };
+            }
+            if let Some(outcome) = self.step(&mut retired) {
+                break outcome;
+            }
+        };
+        self.overdraft += retired.saturating_sub(budget);
+        outcome
+    }
+
+    pub fn execute(&mut self, max_cycle: u64) -> ExecutionOutcome {
+        let mut retired = 0;
+        loop {
+            if self.cpu.state.read(csr::TIME) >= max_cycle {
+                break ExecutionOutcome::BudgetExhausted { retired };
+            }
+            if let Some(outcome) = self.step(&mut retired) {
+                break outcome;
+            }
+        }
+    }
+
+    fn step(&mut self, retired: &mut u64) -> Option<ExecutionOutcome> {
+        match self.cpu.cycle() {
+            Ok(_) => {
+                let mut spf = self.spf.lock().unwrap();
+                *retired += spf.charge.take().unwrap_or(1);
+                let fault = spf.fault.take()?;
+                Some(ExecutionOutcome::Faulted {
+                    retired: *retired,
+                    fault: Fault::Spf(fault),
+                })
+            }
+            Err(
+                Exception::EnvironmentCallFromUMode
+                | Exception::EnvironmentCallFromSMode
+                | Exception::EnvironmentCallFromMMode,
+            ) => {
+                *retired += 1;
+                Some(self.syscall(*retired))
+            }
+            Err(exception) => Some(ExecutionOutcome::Faulted {
+                retired: *retired,
+                fault: Fault::Cpu(exception),
+            }),
+        }
+    }
+
+    fn syscall(&self, retired: u64) -> ExecutionOutcome {
+        let a0 = self.cpu.xregs.read(cpu::REG_A0) as i64;
+        match self.cpu.xregs.read(cpu::REG_A7) {
+            SYS_EXIT => ExecutionOutcome::Exited { retired, code: a0 },
+            SYS_YIELD => ExecutionOutcome::Yielded { retired },
+            SYS_ABORT => ExecutionOutcome::Aborted { retired, code: a0 },
+            number => ExecutionOutcome::BadSyscall { retired, number },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celestial::KeplerianElements;
+    use crate::spf::SPF_SQRT;
+
+    const LINK_MAP_ELF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/link_map.elf");
+
+    /// A machine running `program` from the start of RAM, as if it had
+    /// been loaded with `load_kernel`.
+    fn machine(program: &[u32]) -> BubblyByter {
+        let mut b = BubblyByter::new();
+        b.firmware = program.iter().flat_map(|i| i.to_le_bytes()).collect();
+        b.reset();
+        b
+    }
+
+    #[test]
+    fn budgets_are_counted_in_instructions() {
+        let mut b = machine(&[
+            0x00000013, // nop
+            0x00000013, // nop
+            0x00000013, // nop
+            0x00000073, // ecall        # a7 is 0, SYS_EXIT
+        ]);
+        assert_eq!(
+            b.execute_budget(2),
+            ExecutionOutcome::BudgetExhausted { retired: 2 }
+        );
+        // Carries on from the third instruction.
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Exited {
+                retired: 2,
+                code: 0
+            }
+        );
+    }
+
+    #[test]
+    fn syscalls_end_the_tick() {
+        let mut b = machine(&[
+            0x00100893, // li a7, 1     # SYS_YIELD
+            0x00000073, // ecall
+            0xffd00513, // li a0, -3
+            0x00200893, // li a7, 2     # SYS_ABORT
+            0x00000073, // ecall
+            0x00900893, // li a7, 9
+            0x00000073, // ecall
+        ]);
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Yielded { retired: 2 }
+        );
+        // Without a reset, the next call carries on after the yield.
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Aborted {
+                retired: 3,
+                code: -3
+            }
+        );
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::BadSyscall {
+                retired: 2,
+                number: 9
+            }
+        );
+    }
+
+    #[test]
+    fn nvram_survives_reset() {
+        let mut b = machine(&[
+            0x00500313, // li t1, 5
+            0x01d31313, // slli t1, t1, 29     # NVRAM
+            0x02a00293, // li t0, 42
+            0x00533023, // sd t0, 0(t1)
+            0x00000893, // li a7, 0            # SYS_EXIT
+            0x00000073, // ecall
+        ]);
+        assert!(matches!(
+            b.execute_budget(10),
+            ExecutionOutcome::Exited { code: 0, .. }
+        ));
+        b.reset();
+        assert_eq!(b.save_nvram().unwrap()[..8], 42u64.to_le_bytes());
+    }
+
+    #[test]
+    fn short_nvram_images_are_zero_padded() {
+        let mut b = BubblyByter::new();
+        b.restore_nvram(&[0xff; 64]).unwrap();
+        b.restore_nvram(&[1, 2, 3]).unwrap();
+        let nvram = b.save_nvram().unwrap();
+        assert_eq!(nvram.len() as u64, b.memory_map().nvram.size);
+        assert_eq!(nvram[..3], [1, 2, 3]);
+        assert!(nvram[3..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn oversized_nvram_images_are_rejected() {
+        let mut b = BubblyByter::new();
+        b.restore_nvram(&[7]).unwrap();
+        let capacity = b.memory_map().nvram.size as usize;
+        assert!(matches!(
+            b.restore_nvram(&vec![0; capacity + 1]),
+            Err(NvramError::TooLarge { len, capacity: c }) if len == capacity + 1 && c == capacity
+        ));
+        assert_eq!(b.save_nvram().unwrap()[0], 7);
+    }
+
+    #[test]
+    fn overdraft_is_taken_off_the_next_budget() {
+        let mut b = machine(&[
+            0x00100313, // loop: li t1, 1
+            0x01f31313, //       slli t1, t1, 31
+            0x48030313, //       addi t1, t1, 0x480
+            0x000300e7, //       jalr t1           # sqrt
+            0xff1ff06f, //       j loop
+        ]);
+        let mut costs = CostTable::default();
+        costs.set(SPF_SQRT, 100);
+        b.set_cost_table(costs);
+
+        assert_eq!(
+            b.execute_budget(50),
+            ExecutionOutcome::BudgetExhausted { retired: 103 }
+        );
+        // 53 over: the whole of the next tick goes on paying it back...
+        b.reset();
+        assert_eq!(
+            b.execute_budget(50),
+            ExecutionOutcome::BudgetExhausted { retired: 0 }
+        );
+        // ...and the 3 left come out of the one after.
+        b.reset();
+        assert_eq!(
+            b.execute_budget(50),
+            ExecutionOutcome::BudgetExhausted { retired: 103 }
+        );
+        assert_eq!(b.overdraft, 56);
+    }
+
+    #[test]
+    fn stores_to_rom_fault() {
+        let mut b = machine(&[
+            0x00100313, // li t1, 1
+            0x01f31313, // slli t1, t1, 31
+            0x40030313, // addi t1, t1, 0x400    # the first SPF slot
+            0x00033023, // sd zero, 0(t1)
+        ]);
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Faulted {
+                retired: 3,
+                fault: Fault::Cpu(Exception::StoreAMOAccessFault),
+            }
+        );
+    }
+
+    #[test]
+    fn mmio_reads_the_devices() {
+        let mut b = machine(&[
+            0x00900313, // li t1, 9
+            0x01c31313, // slli t1, t1, 28      # chrono
+            0x00033507, // fld fa0, 0(t1)
+            0xc2257553, // fcvt.l.d a0, fa0
+            0x00000893, // li a7, 0             # SYS_EXIT
+            0x00000073, // ecall
+        ]);
+        b.devices_mut().chrono.current = 42.0;
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Exited {
+                retired: 6,
+                code: 42
+            }
+        );
+    }
+
+    #[test]
+    fn mmio_stores_go_to_the_devices() {
+        let mut b = machine(&[
+            0x00900313, // li t1, 9
+            0x01c31313, // slli t1, t1, 28
+            0x10030313, // addi t1, t1, 0x100   # thrusters.attitude[0].throttle
+            0x00100293, // li t0, 1
+            0x03e29293, // slli t0, t0, 62      # 2.0
+            0x00533023, // sd t0, 0(t1)
+            0x00000893, // li a7, 0             # SYS_EXIT
+            0x00000073, // ecall
+        ]);
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Exited {
+                retired: 8,
+                code: 0
+            }
+        );
+        assert_eq!(b.devices().thrusters.attitude[0].throttle, 1.0);
+    }
+
+    #[test]
+    fn stores_to_read_only_registers_fault() {
+        let mut b = machine(&[
+            0x00900313, // li t1, 9
+            0x01c31313, // slli t1, t1, 28      # chrono
+            0x00033023, // sd zero, 0(t1)
+        ]);
+        b.devices_mut().chrono.current = 42.0;
+        assert_eq!(
+            b.execute_budget(10),
+            ExecutionOutcome::Faulted {
+                retired: 2,
+                fault: Fault::Cpu(Exception::StoreAMOAccessFault),
+            }
+        );
+        assert_eq!(b.devices().chrono.current, 42.0);
+    }
+
+    /// The offset in `elf` of the program header for the segment linked at
+    /// `vaddr`.
+    fn program_header(elf: &[u8], vaddr: u64) -> usize {
+        let phoff = u64::from_le_bytes(elf[32..40].try_into().unwrap()) as usize;
+        let phnum = u16::from_le_bytes(elf[56..58].try_into().unwrap()) as usize;
+        (0..phnum)
+            .map(|i| phoff + i * 56)
+            .find(|ph| elf[ph + 16..ph + 24] == vaddr.to_le_bytes())
+            .expect("there's a segment at vaddr")
+    }
+
+    /// Moves the segment linked at `vaddr` in `elf` to `to`.
+    fn move_segment(elf: &mut [u8], vaddr: u64, to: u64) {
+        let ph = program_header(elf, vaddr);
+        elf[ph + 16..ph + 24].copy_from_slice(&to.to_le_bytes());
+        elf[ph + 24..ph + 32].copy_from_slice(&to.to_le_bytes());
+    }
+
+    #[test]
+    fn load_elf_names_calls_from_the_link_map() {
+        let mut b = BubblyByter::new();
+        b.load_elf(LINK_MAP_ELF).unwrap();
+        assert_eq!(
+            b.execute_budget(100),
+            ExecutionOutcome::Faulted {
+                retired: 13,
+                fault: Fault::Spf(SpfFault::Unimplemented {
+                    addr: 0x8000_0438,
+                    symbol: Some("memset".to_string()),
+                }),
+            }
+        );
+        // sqrt(16.0), stored through `saved` at the start of NVRAM.
+        assert_eq!(b.save_nvram().unwrap()[..8], 4.0f64.to_le_bytes());
+    }
+
+    #[test]
+    fn load_elf_rejects_other_machines() {
+        let elf = std::fs::read(LINK_MAP_ELF).unwrap();
+        let mut b = BubblyByter::new();
+
+        let mut elf32 = elf.clone();
+        elf32[4] = 1; // ELFCLASS32
+        assert!(matches!(
+            b.load_elf_bytes(&elf32),
+            Err(ElfError::NotRiscv64)
+        ));
+
+        let mut x86_64 = elf.clone();
+        x86_64[18..20].copy_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+        assert!(matches!(
+            b.load_elf_bytes(&x86_64),
+            Err(ElfError::NotRiscv64)
+        ));
+    }
+
+    #[test]
+    fn load_elf_rejects_segments_outside_the_memory_map() {
+        let elf = std::fs::read(LINK_MAP_ELF).unwrap();
+        let mut b = BubblyByter::new();
+
+        // Only RAM can be loaded with contents...
+        let mut code_in_rom = elf.clone();
+        move_segment(&mut code_in_rom, 0x4000_0000, 0x8000_8000);
+        assert!(matches!(
+            b.load_elf_bytes(&code_in_rom),
+            Err(ElfError::OutsideRam {
+                index: 0,
+                vaddr: 0x8000_8000,
+                memsz: 0x3a
+            })
+        ));
+
+        // ...and space can only be reserved inside one of the regions.
+        let mut unmapped = elf.clone();
+        move_segment(&mut unmapped, 0xa000_0000, 0x1000);
+        assert!(matches!(
+            b.load_elf_bytes(&unmapped),
+            Err(ElfError::OutsideRam {
+                index: 1,
+                vaddr: 0x1000,
+                ..
+            })
+        ));
+
+        let mut past_nvram = elf.clone();
+        move_segment(&mut past_nvram, 0xa000_0000, 0xa000_8000);
+        assert!(matches!(
+            b.load_elf_bytes(&past_nvram),
+            Err(ElfError::OutsideRam {
+                index: 1,
+                vaddr: 0xa000_8000,
+                memsz: 0x1_0000
+            })
+        ));
+    }
+
+    #[test]
+    fn load_elf_rejects_images_bigger_than_ram() {
+        let mut elf = std::fs::read(LINK_MAP_ELF).unwrap();
+        let ph = program_header(&elf, 0x4000_0000);
+        elf[ph + 40..ph + 48].copy_from_slice(&0x2_0000u64.to_le_bytes());
+        let mut b = BubblyByter::new();
+        assert!(matches!(
+            b.load_elf_bytes(&elf),
+            Err(ElfError::TooLarge {
+                size: 0x2_0000,
+                capacity: 0x1_0000
+            })
+        ));
+    }
+
+    #[test]
+    fn k_pos_reads_elements_out_of_the_celestial_table() {
+        let mut b = machine(&[
+            0x00500513, // li a0, 5
+            0x01d51513, // slli a0, a0, 29      # res: the start of NVRAM
+            0x00100593, // li a1, 1
+            0x01f59593, // slli a1, a1, 31
+            0x000012b7, // lui t0, 1
+            0x005585b3, // add a1, a1, t0       # k: &celestials[0].elements
+            0x00100313, // li t1, 1
+            0x01f31313, // slli t1, t1, 31
+            0x41030313, // addi t1, t1, 0x410
+            0x000300e7, // jalr t1              # k_pos(res, k, 0.0)
+            0x00000513, // li a0, 0
+            0x00000893, // li a7, 0             # SYS_EXIT
+            0x00000073, // ecall
+        ]);
+        let elements = KeplerianElements::from_state_vector(
+            [7.0e6, 1.0e6, 5.0e5],
+            [-1000.0, 8500.0, 1500.0],
+            3.986e14,
+            0.0,
+        );
+        b.load_celestials(&[Celestial {
+            elements: elements.clone(),
+            ..Celestial::default()
+        }])
+        .unwrap();
+
+        assert!(matches!(
+            b.execute_budget(100),
+            ExecutionOutcome::Exited { code: 0, .. }
+        ));
+        let nvram = b.save_nvram().unwrap();
+        let position: Vec<f64> = nvram[..24]
+            .chunks(8)
+            .map(|d| f64::from_le_bytes(d.try_into().unwrap()))
+            .collect();
+        assert_eq!(position, elements.position_at(0.0));
+    }
 }
@@ -0,0 +1,18 @@
+// Writes memmap.ld and memmap.h into the given directory for the
+// bubbly_byter_cc build.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use bubbly_byter::memory::MemoryMap;
+
+fn main() -> io::Result<()> {
+    let out_dir = PathBuf::from(env::args().nth(1).unwrap_or_else(|| ".".to_string()));
+    let map = MemoryMap::default();
+
+    fs::write(out_dir.join("memmap.ld"), map.linker_script())?;
+    fs::write(out_dir.join("memmap.h"), map.c_header())?;
+    Ok(())
+}
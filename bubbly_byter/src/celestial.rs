@@ -0,0 +1,370 @@
+// The celestial table in ROM. Mirrors `struct celobjdat` in lib.h, which
+// is packed, so the layout here is field after field with no padding.
+
+use rvemu::exception::Exception;
+use std::f64::consts::TAU;
+
+use crate::math::{cross3, dot3, kepler, norm3, scale3, sub3};
+
+/// Number of entries the linker reserves for `celestials`.
+pub const MAX_CELESTIALS: usize = 512;
+
+pub const NAME_LEN: usize = 32;
+
+/// `sizeof(struct celobjdat)`: the elements, mass, diameter and the name.
+pub const CELOBJDAT_SIZE: usize = KEPLARIAN_ELEMENTS_LEN * 8 + 8 + 8 + NAME_LEN;
+
+#[derive(Debug)]
+pub enum CelestialError {
+    TooMany { count: usize, capacity: usize },
+    Memory(Exception),
+}
+
+impl From<Exception> for CelestialError {
+    fn from(e: Exception) -> Self {
+        CelestialError::Memory(e)
+    }
+}
+
+/// Seconds in a day, for turning Julian dates into seconds.
+const DAY: f64 = 86400.0;
+
+/// Doubles in `struct keplarian_elements`.
+pub const KEPLARIAN_ELEMENTS_LEN: usize = 12;
+
+/// `struct keplarian_elements`. Lengths are in meters, angles in radians
+/// and `epoch` is a Julian date, the same as the chronometer's.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeplerianElements {
+    /// Eccentricity
+    pub e: f64,
+    /// Semi-major axis
+    pub a: f64,
+    /// Semi-minor axis
+    pub b: f64,
+    /// Semi-latus rectum
+    pub p: f64,
+    /// Apoapsis radius
+    pub ra: f64,
+    /// Periapsis radius
+    pub rp: f64,
+    /// Inclination
+    pub i: f64,
+    /// Longitude of the ascending node
+    pub lan: f64,
+    /// Argument of periapsis
+    pub argp: f64,
+    /// Mean anomaly at `epoch`
+    pub m0: f64,
+    pub epoch: f64,
+    /// The parent's gravitational parameter, G * M, in m^3 s^-2.
+    pub mu: f64,
+}
+
+impl KeplerianElements {
+    /// The orbit of a body at `position` moving at `velocity` at Julian
+    /// date `epoch`, both relative to a parent whose gravitational
+    /// parameter (G * M) is `mu`. Escape trajectories have no apoapsis or
+    /// semi-minor axis, so `ra` is infinite and `b` is NaN.
+    pub fn from_state_vector(
+        position: [f64; 3],
+        velocity: [f64; 3],
+        mu: f64,
+        epoch: f64,
+    ) -> KeplerianElements {
+        let r = norm3(position);
+        let h = cross3(position, velocity);
+        let h_hat = scale3(h, 1.0 / norm3(h));
+
+        let ev = sub3(
+            scale3(cross3(velocity, h), 1.0 / mu),
+            scale3(position, 1.0 / r),
+        );
+        let e = norm3(ev);
+
+        let energy = dot3(velocity, velocity) / 2.0 - mu / r;
+        let a = -mu / (2.0 * energy);
+        let p = dot3(h, h) / mu;
+        let (b, ra) = if e < 1.0 {
+            (a * (1.0 - e * e).sqrt(), p / (1.0 - e))
+        } else {
+            (f64::NAN, f64::INFINITY)
+        };
+
+        // Direction of the ascending node. An equatorial orbit doesn't
+        // have one, so angles are measured from +x instead.
+        let node = [-h[1], h[0], 0.0];
+        let node_hat = if norm3(node) > 1e-12 * norm3(h) {
+            scale3(node, 1.0 / norm3(node))
+        } else {
+            [1.0, 0.0, 0.0]
+        };
+        let i = (h_hat[2]).clamp(-1.0, 1.0).acos();
+        let lan = node_hat[1].atan2(node_hat[0]);
+
+        // Angles in the orbital plane, from the node, in the direction of
+        // motion.
+        let angle_from_node =
+            |v: [f64; 3]| dot3(cross3(node_hat, v), h_hat).atan2(dot3(node_hat, v));
+        let u = angle_from_node(position);
+        // A circular orbit has no periapsis; put it at the node.
+        let argp = if e > 1e-12 { angle_from_node(ev) } else { 0.0 };
+        let nu = u - argp;
+
+        let m0 = if e < 1.0 {
+            let ea = ((1.0 - e * e).sqrt() * nu.sin()).atan2(e + nu.cos());
+            ea - e * ea.sin()
+        } else {
+            f64::NAN
+        };
+
+        KeplerianElements {
+            e,
+            a,
+            b,
+            p,
+            ra,
+            rp: p / (1.0 + e),
+            i,
+            lan: lan.rem_euclid(TAU),
+            argp: argp.rem_euclid(TAU),
+            m0: m0.rem_euclid(TAU),
+            epoch,
+            mu,
+        }
+    }
+
+    /// Position relative to the parent at Julian date `t`. Only elliptical
+    /// orbits can be propagated; anything else is NaN.
+    pub fn position_at(&self, t: f64) -> [f64; 3] {
+        let n = (self.mu / self.a.powi(3)).sqrt();
+        let m = self.m0 + n * (t - self.epoch) * DAY;
+        let ea = kepler(m, self.e);
+
+        // In the orbital plane, with periapsis along +x.
+        let x = self.a * (ea.cos() - self.e);
+        let y = self.a * (1.0 - self.e * self.e).sqrt() * ea.sin();
+
+        let (so, co) = self.lan.sin_cos();
+        let (si, ci) = self.i.sin_cos();
+        let (sw, cw) = self.argp.sin_cos();
+        [
+            (co * cw - so * sw * ci) * x - (co * sw + so * cw * ci) * y,
+            (so * cw + co * sw * ci) * x + (co * cw * ci - so * sw) * y,
+            sw * si * x + cw * si * y,
+        ]
+    }
+
+    pub fn to_doubles(&self) -> [f64; KEPLARIAN_ELEMENTS_LEN] {
+        [
+            self.e, self.a, self.b, self.p, self.ra, self.rp, self.i, self.lan, self.argp, self.m0,
+            self.epoch, self.mu,
+        ]
+    }
+
+    pub fn from_doubles(d: [f64; KEPLARIAN_ELEMENTS_LEN]) -> KeplerianElements {
+        let [e, a, b, p, ra, rp, i, lan, argp, m0, epoch, mu] = d;
+        KeplerianElements {
+            e,
+            a,
+            b,
+            p,
+            ra,
+            rp,
+            i,
+            lan,
+            argp,
+            m0,
+            epoch,
+            mu,
+        }
+    }
+}
+
+/// One entry of `celestials`. Lengths are in meters, mass in kilograms.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Celestial {
+    pub elements: KeplerianElements,
+    pub mass: f64,
+    pub diameter: f64,
+    /// Truncated to 31 bytes so the guest always sees a NUL terminator.
+    pub name: String,
+}
+
+impl Celestial {
+    pub fn to_bytes(&self) -> [u8; CELOBJDAT_SIZE] {
+        let mut out = [0; CELOBJDAT_SIZE];
+        let mut doubles = self.elements.to_doubles().to_vec();
+        doubles.extend([self.mass, self.diameter]);
+        for (i, d) in doubles.iter().enumerate() {
+            out[i * 8..i * 8 + 8].copy_from_slice(&d.to_le_bytes());
+        }
+        let name = self.name.as_bytes();
+        let len = name.len().min(NAME_LEN - 1);
+        let at = doubles.len() * 8;
+        out[at..at + len].copy_from_slice(&name[..len]);
+        out
+    }
+}
+
+/// The whole table as it's laid out in ROM. Unused entries are zeroed.
+pub fn table_bytes(bodies: &[Celestial]) -> Result<Vec<u8>, CelestialError> {
+    if bodies.len() > MAX_CELESTIALS {
+        return Err(CelestialError::TooMany {
+            count: bodies.len(),
+            capacity: MAX_CELESTIALS,
+        });
+    }
+    let mut table = vec![0; MAX_CELESTIALS * CELOBJDAT_SIZE];
+    for (entry, body) in table.chunks_exact_mut(CELOBJDAT_SIZE).zip(bodies) {
+        entry.copy_from_slice(&body.to_bytes());
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU_EARTH: f64 = 3.986004418e14;
+    /// Julian dates this big only resolve to tens of microseconds, too
+    /// coarse for differencing positions, so the orbits start at 0.
+    const EPOCH: f64 = 0.0;
+
+    fn assert_near(got: [f64; 3], want: [f64; 3], tolerance: f64) {
+        assert!(
+            norm3(sub3(got, want)) <= tolerance,
+            "{:?} != {:?}",
+            got,
+            want
+        );
+    }
+
+    /// Where `k` puts the body at `t` and how fast it's going there, by
+    /// central difference.
+    fn state_at(k: &KeplerianElements, t: f64) -> ([f64; 3], [f64; 3]) {
+        let h = 1.0;
+        let ahead = k.position_at(t + h / DAY);
+        let behind = k.position_at(t - h / DAY);
+        (k.position_at(t), scale3(sub3(ahead, behind), 0.5 / h))
+    }
+
+    #[test]
+    fn circular_orbit_round_trips() {
+        let r = 7.0e6;
+        let v = (MU_EARTH / r).sqrt();
+        let (s, c) = 0.5f64.sin_cos();
+        let k = KeplerianElements::from_state_vector(
+            [r, 0.0, 0.0],
+            [0.0, v * c, v * s],
+            MU_EARTH,
+            EPOCH,
+        );
+        assert!(k.e < 1e-9);
+        assert!((k.a - r).abs() < 1e-3);
+        assert!((k.i - 0.5).abs() < 1e-12);
+
+        assert_near(k.position_at(EPOCH), [r, 0.0, 0.0], 1e-3);
+        // A quarter of the way round, it's crossed to the far side of the
+        // plane it was launched along.
+        let period = TAU * (r.powi(3) / MU_EARTH).sqrt();
+        assert_near(
+            k.position_at(EPOCH + period / 4.0 / DAY),
+            [0.0, r * c, r * s],
+            1e-3,
+        );
+    }
+
+    #[test]
+    fn eccentric_orbit_round_trips() {
+        let position = [7.0e6, 1.0e6, 5.0e5];
+        let velocity = [-1000.0, 8500.0, 1500.0];
+        let k = KeplerianElements::from_state_vector(position, velocity, MU_EARTH, EPOCH);
+        assert!(k.e > 0.1 && k.e < 1.0, "e = {}", k.e);
+
+        let (p, v) = state_at(&k, EPOCH);
+        assert_near(p, position, 1e-3);
+        assert_near(v, velocity, 0.01);
+
+        // Anywhere along the orbit, the state there describes the same
+        // orbit.
+        let period = TAU * (k.a.powi(3) / MU_EARTH).sqrt();
+        for f in [0.1, 0.37, 0.5, 0.8, 1.0] {
+            let t = EPOCH + f * period / DAY;
+            let (p, v) = state_at(&k, t);
+            let again = KeplerianElements::from_state_vector(p, v, MU_EARTH, t);
+            assert!((again.a - k.a).abs() < 1e-6 * k.a);
+            assert!((again.e - k.e).abs() < 1e-6);
+            assert!((again.i - k.i).abs() < 1e-6);
+            assert!((again.lan - k.lan).abs() < 1e-6);
+            assert!((again.argp - k.argp).abs() < 1e-6);
+        }
+        assert_near(k.position_at(EPOCH + period / DAY), position, 1e-3);
+    }
+
+    #[test]
+    fn escape_trajectories_have_no_apoapsis() {
+        let k = KeplerianElements::from_state_vector(
+            [7.0e6, 0.0, 0.0],
+            [0.0, 2.0e4, 0.0],
+            MU_EARTH,
+            EPOCH,
+        );
+        assert!(k.e > 1.0);
+        assert_eq!(k.ra, f64::INFINITY);
+        assert!(k.b.is_nan());
+        assert!(k.position_at(EPOCH)[0].is_nan());
+    }
+
+    #[test]
+    fn celobjdat_layout() {
+        // struct celobjdat: 12 doubles of elements, mass, diameter, name.
+        assert_eq!(CELOBJDAT_SIZE, 144);
+        let elements = KeplerianElements::from_doubles(std::array::from_fn(|i| i as f64 + 1.0));
+        let body = Celestial {
+            elements,
+            mass: 100.0,
+            diameter: 200.0,
+            name: "a name that's too long for the table".to_string(),
+        };
+        let b = body.to_bytes();
+        let double_at = |at: usize| f64::from_le_bytes(b[at..at + 8].try_into().unwrap());
+        for i in 0..KEPLARIAN_ELEMENTS_LEN {
+            assert_eq!(double_at(8 * i), i as f64 + 1.0);
+        }
+        assert_eq!(double_at(0), body.elements.e);
+        assert_eq!(double_at(88), body.elements.mu);
+        assert_eq!(double_at(96), 100.0);
+        assert_eq!(double_at(104), 200.0);
+        assert_eq!(&b[112..143], &body.name.as_bytes()[..31]);
+        assert_eq!(b[143], 0);
+    }
+
+    #[test]
+    fn table_is_every_entry_back_to_back() {
+        let bodies: Vec<_> = (0..3)
+            .map(|i| Celestial {
+                mass: i as f64,
+                name: format!("body {}", i),
+                ..Celestial::default()
+            })
+            .collect();
+        let table = table_bytes(&bodies).unwrap();
+        assert_eq!(table.len(), MAX_CELESTIALS * CELOBJDAT_SIZE);
+        for (i, body) in bodies.iter().enumerate() {
+            assert_eq!(
+                table[i * CELOBJDAT_SIZE..(i + 1) * CELOBJDAT_SIZE],
+                body.to_bytes()
+            );
+        }
+        assert!(table[3 * CELOBJDAT_SIZE..].iter().all(|b| *b == 0));
+
+        assert!(matches!(
+            table_bytes(&vec![Celestial::default(); MAX_CELESTIALS + 1]),
+            Err(CelestialError::TooMany {
+                count: 513,
+                capacity: MAX_CELESTIALS
+            })
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+// How many instructions of a ship's budget each system provided function
+// costs. Loaded from a file so game balance doesn't need a rebuild:
+//
+//     # anything not listed costs `default`
+//     default = 1
+//     atan2 = 4
+//     vcross33 = 2
+//     0x80000500 = 4
+//
+// Keys are the names from `link.ld` or addresses. Costs are at least 1,
+// the `jal` that makes the call.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::spf::spf_address;
+
+#[derive(Debug)]
+pub enum CostTableError {
+    Io(io::Error),
+    /// `line` is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl From<io::Error> for CostTableError {
+    fn from(e: io::Error) -> Self {
+        CostTableError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostTable {
+    default: u64,
+    costs: HashMap<u64, u64>,
+}
+
+impl Default for CostTable {
+    /// Every call is a single cycle.
+    fn default() -> Self {
+        CostTable {
+            default: 1,
+            costs: HashMap::new(),
+        }
+    }
+}
+
+impl CostTable {
+    pub fn cost(&self, addr: u64) -> u64 {
+        self.costs.get(&addr).copied().unwrap_or(self.default)
+    }
+
+    pub fn set(&mut self, addr: u64, cost: u64) {
+        self.costs.insert(addr, cost.max(1));
+    }
+
+    /// What functions that aren't listed cost.
+    pub fn default_cost(&self) -> u64 {
+        self.default
+    }
+
+    pub fn set_default(&mut self, cost: u64) {
+        self.default = cost.max(1);
+    }
+
+    pub fn parse(text: &str) -> Result<CostTable, CostTableError> {
+        let mut table = CostTable::default();
+        for (i, line) in text.lines().enumerate() {
+            let err = |message: String| CostTableError::Parse {
+                line: i + 1,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `name = cost`".to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let cost: u64 = value
+                .parse()
+                .map_err(|_| err(format!("`{}` isn't a whole number", value)))?;
+            if cost == 0 {
+                return Err(err("costs must be at least 1".to_string()));
+            }
+
+            if key == "default" {
+                table.set_default(cost);
+                continue;
+            }
+            let addr = match key.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16)
+                    .map_err(|_| err(format!("`{}` isn't an address", key)))?,
+                None => spf_address(key)
+                    .ok_or_else(|| err(format!("no system provided function `{}`", key)))?,
+            };
+            table.set(addr, cost);
+        }
+        Ok(table)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CostTable, CostTableError> {
+        CostTable::parse(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spf::{SPF_ATAN2, SPF_SQRT, SPF_VCROSS33};
+
+    #[test]
+    fn parses_names_and_addresses() {
+        let table = CostTable::parse(
+            "# anything not listed costs `default`\n\
+             default = 2\n\
+             \n\
+             vcross33 = 3 # the v twins cost the same\n\
+             0x80000500 = 4\n",
+        )
+        .unwrap();
+        assert_eq!(table.default_cost(), 2);
+        assert_eq!(table.cost(SPF_VCROSS33), 3);
+        assert_eq!(table.cost(SPF_ATAN2), 4);
+        assert_eq!(table.cost(SPF_SQRT), 2);
+    }
+
+    #[test]
+    fn costs_are_at_least_one() {
+        let mut table = CostTable::default();
+        table.set(SPF_SQRT, 0);
+        table.set_default(0);
+        assert_eq!(table.cost(SPF_SQRT), 1);
+        assert_eq!(table.default_cost(), 1);
+
+        assert!(matches!(
+            CostTable::parse("sqrt = 0"),
+            Err(CostTableError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            CostTable::parse("\ndefault = 0"),
+            Err(CostTableError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_what_it_cant_read() {
+        for text in ["sqrt", "sqrt = fast", "sqrt = -1", "nothing = 2", "0xg = 2"] {
+            assert!(
+                matches!(
+                    CostTable::parse(text),
+                    Err(CostTableError::Parse { line: 1, .. })
+                ),
+                "{:?}",
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,175 @@
+// `adf`: the automatic direction finder. The guest tunes each channel to a
+// nav beacon's frequency and reads back where the beacon is relative to
+// the ship.
+//
+// struct adf_channel { double frequency; double locked; double distance; double pitch; double yaw; }
+// struct { struct adf_channel channel[N_ADF_CHANNELS]; }
+
+use super::ship_axes;
+use crate::math::{dot3, norm3, sub3};
+
+pub const OFFSET: u64 = 0x300;
+pub const N_CHANNELS: usize = 4;
+pub const CHANNEL_SIZE: u64 = 5 * 8;
+pub const SIZE: u64 = N_CHANNELS as u64 * CHANNEL_SIZE;
+
+/// A nav beacon, placed by the host from the simulation.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Beacon {
+    pub frequency: f64,
+    /// Meters from the solar system barycenter.
+    pub position: [f64; 3],
+    /// Meters. A ship further away than this can't hear the beacon.
+    pub range: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AdfChannel {
+    /// Set by the guest. Zero is off.
+    pub frequency: f64,
+    /// Whether a beacon is transmitting on `frequency`. When there isn't
+    /// one, the bearing is NaN.
+    pub locked: bool,
+    /// Meters.
+    pub distance: f64,
+    /// Radians above the ship's nose, -pi/2 to pi/2.
+    pub pitch: f64,
+    /// Radians left of the ship's nose, -pi to pi.
+    pub yaw: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Adf {
+    pub channels: [AdfChannel; N_CHANNELS],
+}
+
+impl Adf {
+    /// Takes bearings for every tuned channel from the ship's true
+    /// `position` and `orientation`. A channel locks on to the first beacon
+    /// in `beacons` with exactly its frequency that's in range.
+    pub fn update(&mut self, position: [f64; 3], orientation: [f64; 3], beacons: &[Beacon]) {
+        let [forward, left, up] = ship_axes(orientation);
+        for ch in &mut self.channels {
+            let beacon = beacons
+                .iter()
+                .filter(|b| ch.frequency != 0.0 && b.frequency == ch.frequency)
+                .map(|b| (sub3(b.position, position), b.range))
+                .find(|(d, range)| norm3(*d) <= *range);
+            match beacon {
+                Some((d, _)) => {
+                    let (x, y, z) = (dot3(d, forward), dot3(d, left), dot3(d, up));
+                    ch.locked = true;
+                    ch.distance = norm3(d);
+                    ch.pitch = z.atan2(x.hypot(y));
+                    ch.yaw = y.atan2(x);
+                }
+                None => {
+                    ch.locked = false;
+                    ch.distance = f64::NAN;
+                    ch.pitch = f64::NAN;
+                    ch.yaw = f64::NAN;
+                }
+            }
+        }
+    }
+
+    /// The `i`th double of the struct the guest sees.
+    pub fn register(&self, i: usize) -> f64 {
+        let ch = &self.channels[i / 5];
+        let locked = if ch.locked { 1.0 } else { 0.0 };
+        [ch.frequency, locked, ch.distance, ch.pitch, ch.yaw][i % 5]
+    }
+
+    /// The guest setting the `i`th double. Only the frequencies can be
+    /// set, and a retuned channel keeps its old bearing until the next
+    /// `update`. Returns whether the register is writable.
+    pub fn set_register(&mut self, i: usize, value: f64) -> bool {
+        if !i.is_multiple_of(5) {
+            return false;
+        }
+        self.channels[i / 5].frequency = value;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    const SHIP: [f64; 3] = [1.0e9, 2.0e9, 0.0];
+    /// Nose along +y, so +x is to the right.
+    const ORIENTATION: [f64; 3] = [0.0, 0.0, FRAC_PI_2];
+
+    fn beacon(offset: [f64; 3]) -> Beacon {
+        Beacon {
+            frequency: 110.5,
+            position: [0, 1, 2].map(|i| SHIP[i] + offset[i]),
+            range: 1.0e6,
+        }
+    }
+
+    /// Channel 0, tuned to 110.5, after an update with `beacons` around.
+    fn bearing(beacons: &[Beacon]) -> AdfChannel {
+        let mut adf = Adf::default();
+        adf.set_register(0, 110.5);
+        adf.update(SHIP, ORIENTATION, beacons);
+        adf.channels[0].clone()
+    }
+
+    fn assert_angle(got: f64, want: f64) {
+        assert!((got - want).abs() < 1e-9, "{} != {}", got, want);
+    }
+
+    #[test]
+    fn beacon_ahead() {
+        let ch = bearing(&[beacon([0.0, 3000.0, 4000.0])]);
+        assert!(ch.locked);
+        assert_eq!(ch.distance, 5000.0);
+        assert_angle(ch.pitch, 4.0f64.atan2(3.0));
+        assert_angle(ch.yaw, 0.0);
+    }
+
+    #[test]
+    fn beacon_abeam() {
+        let ch = bearing(&[beacon([-2000.0, 0.0, 0.0])]);
+        assert!(ch.locked);
+        assert_angle(ch.pitch, 0.0);
+        assert_angle(ch.yaw, FRAC_PI_2);
+
+        let ch = bearing(&[beacon([2000.0, 0.0, 0.0])]);
+        assert_angle(ch.yaw, -FRAC_PI_2);
+    }
+
+    #[test]
+    fn beacon_behind() {
+        let ch = bearing(&[beacon([-1.0, -2000.0, 0.0])]);
+        assert!(ch.locked);
+        assert_angle(ch.pitch, 0.0);
+        assert!((ch.yaw.abs() - std::f64::consts::PI).abs() < 1e-3);
+    }
+
+    #[test]
+    fn beacon_out_of_range() {
+        let ch = bearing(&[beacon([0.0, 2.0e6, 0.0])]);
+        assert!(!ch.locked);
+        assert!(ch.distance.is_nan() && ch.pitch.is_nan() && ch.yaw.is_nan());
+        assert_eq!(ch.frequency, 110.5);
+
+        // One out of range doesn't hide another on the same frequency.
+        let ch = bearing(&[beacon([0.0, 2.0e6, 0.0]), beacon([0.0, 0.0, 10.0])]);
+        assert!(ch.locked);
+        assert_eq!(ch.distance, 10.0);
+    }
+
+    #[test]
+    fn untuned_channels_dont_lock() {
+        let mut adf = Adf::default();
+        let mut silent = beacon([0.0, 10.0, 0.0]);
+        silent.frequency = 0.0;
+        adf.update(SHIP, ORIENTATION, &[silent]);
+        assert!(adf.channels.iter().all(|ch| !ch.locked));
+        assert_eq!(adf.register(1), 0.0);
+        assert!(!adf.set_register(1, 1.0));
+    }
+}
@@ -0,0 +1,20 @@
+// `chrono`: the ship's chronometer.
+//
+// struct { double current; }
+
+pub const OFFSET: u64 = 0x0;
+pub const SIZE: u64 = 8;
+
+/// Read only to the guest.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Chrono {
+    /// The simulation's Julian date.
+    pub current: f64,
+}
+
+impl Chrono {
+    /// The `i`th double of the struct the guest sees.
+    pub fn register(&self, _i: usize) -> f64 {
+        self.current
+    }
+}
@@ -0,0 +1,126 @@
+// `fuel`: the propellant tank and what the craft weighs.
+//
+// struct { double propellant; double capacity; double mass; double cargo_mass; double max_cargo_mass; }
+
+use super::Thrust;
+
+pub const OFFSET: u64 = 0x400;
+pub const SIZE: u64 = 5 * 8;
+
+/// Masses are in kilograms. All of it is read only to the guest.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Fuel {
+    pub propellant: f64,
+    pub capacity: f64,
+    /// The craft without propellant or cargo.
+    pub dry_mass: f64,
+    pub cargo_mass: f64,
+    pub max_cargo_mass: f64,
+}
+
+/// The result of firing the thrusters for a while.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Burn {
+    /// Change in velocity along the ship's axes, m s^-1.
+    pub delta_v: [f64; 3],
+    /// Kilograms used.
+    pub propellant: f64,
+    /// Seconds the thrusters actually fired for; less than asked for if
+    /// the tank ran dry.
+    pub duration: f64,
+}
+
+impl Fuel {
+    /// The whole craft: dry mass, cargo and propellant.
+    pub fn mass(&self) -> f64 {
+        self.dry_mass + self.cargo_mass + self.propellant
+    }
+
+    /// Fires `thrust` for `dt` seconds, or until the tank is empty,
+    /// burning propellant as it goes. The craft gets lighter during the
+    /// burn, so the delta-v comes from the rocket equation rather than
+    /// force over mass. A burn that would leave nothing of the craft,
+    /// which only happens if it has no dry mass or cargo, is refused
+    /// rather than given an infinite delta-v.
+    pub fn burn(&mut self, thrust: &Thrust, dt: f64) -> Burn {
+        let mass_flow_rate = thrust.mass_flow_rate;
+        if mass_flow_rate <= 0.0 || self.propellant <= 0.0 {
+            return Burn::default();
+        }
+        let duration = dt.min(self.propellant / mass_flow_rate);
+        let propellant = mass_flow_rate * duration;
+        let m0 = self.mass();
+        let m1 = m0 - propellant;
+        if m1 <= 0.0 {
+            return Burn::default();
+        }
+        // Each thruster's force is its own mass flow times exhaust
+        // velocity, so integrating F / m(t) over the burn gives
+        // F / mdot * ln(m0 / m1) for every one of them.
+        let per_newton = (m0 / m1).ln() / mass_flow_rate;
+        self.propellant = (self.propellant - propellant).max(0.0);
+        Burn {
+            delta_v: thrust.force.map(|f| f * per_newton),
+            propellant,
+            duration,
+        }
+    }
+
+    /// The `i`th double of the struct the guest sees.
+    pub fn register(&self, i: usize) -> f64 {
+        [
+            self.propellant,
+            self.capacity,
+            self.mass(),
+            self.cargo_mass,
+            self.max_cargo_mass,
+        ][i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thrust(force: f64, mass_flow_rate: f64) -> Thrust {
+        Thrust {
+            force: [force, 0.0, 0.0],
+            mass_flow_rate,
+            ..Thrust::default()
+        }
+    }
+
+    #[test]
+    fn burn_follows_the_rocket_equation() {
+        let mut fuel = Fuel {
+            propellant: 100.0,
+            capacity: 100.0,
+            dry_mass: 100.0,
+            ..Fuel::default()
+        };
+        // 3000 m s^-1 exhaust velocity until the tank runs dry at 100 s,
+        // halving the mass
+        let burn = fuel.burn(&thrust(3000.0, 1.0), 200.0);
+        assert_eq!(burn.duration, 100.0);
+        assert_eq!(burn.propellant, 100.0);
+        assert!((burn.delta_v[0] - 3000.0 * 2f64.ln()).abs() < 1e-9);
+        assert_eq!(fuel.propellant, 0.0);
+    }
+
+    #[test]
+    fn burn_that_would_leave_no_mass_is_refused() {
+        let mut fuel = Fuel {
+            propellant: 10.0,
+            capacity: 10.0,
+            ..Fuel::default()
+        };
+        let burn = fuel.burn(&thrust(3000.0, 1.0), 20.0);
+        assert_eq!(burn, Burn::default());
+        assert_eq!(fuel.propellant, 10.0);
+
+        // Burns that leave some propellant are still fine.
+        let burn = fuel.burn(&thrust(3000.0, 1.0), 5.0);
+        assert!(burn.delta_v[0].is_finite());
+        assert_eq!(fuel.propellant, 5.0);
+    }
+}
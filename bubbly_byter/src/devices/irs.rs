@@ -0,0 +1,178 @@
+// `irs`: the inertial reference system, where the ship thinks it is.
+//
+// struct { double position[3]; double velocity[3]; double orientation[3]; }
+
+use super::rng::Rng;
+
+pub const OFFSET: u64 = 0x200;
+pub const SIZE: u64 = 9 * 8;
+
+/// How good a unit is. Noise is a standard deviation added fresh to every
+/// reading. Drift is the standard deviation of a random walk in the
+/// reading's bias, taken one step per update, so errors build up the
+/// longer the unit runs. The default is a perfect unit.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IrsModel {
+    /// Meters.
+    pub position_noise: f64,
+    pub position_drift: f64,
+    /// Meters per second.
+    pub velocity_noise: f64,
+    pub velocity_drift: f64,
+    /// Radians.
+    pub orientation_noise: f64,
+    pub orientation_drift: f64,
+}
+
+/// Position and velocity are in meters and meters per second from the
+/// solar system barycenter. Orientation is pitch, roll and yaw in radians.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Irs {
+    pub model: IrsModel,
+    rng: Rng,
+    /// What the guest sees: the truth, plus bias, plus noise.
+    reading: [f64; 9],
+    bias: [f64; 9],
+}
+
+impl Irs {
+    pub fn new(model: IrsModel, seed: u64) -> Irs {
+        Irs {
+            model,
+            rng: Rng::new(seed),
+            reading: [0.0; 9],
+            bias: [0.0; 9],
+        }
+    }
+
+    /// Takes a new reading of the ship's true state from the physics.
+    pub fn update(&mut self, position: [f64; 3], velocity: [f64; 3], orientation: [f64; 3]) {
+        let m = &self.model;
+        let (noise, drift) = (
+            [m.position_noise, m.velocity_noise, m.orientation_noise],
+            [m.position_drift, m.velocity_drift, m.orientation_drift],
+        );
+        let truth = [position, velocity, orientation].concat();
+        for i in 0..9 {
+            self.bias[i] += drift[i / 3] * self.rng.gaussian();
+            self.reading[i] = truth[i] + self.bias[i] + noise[i / 3] * self.rng.gaussian();
+        }
+    }
+
+    /// Position, velocity and orientation as the guest sees them.
+    pub fn reading(&self) -> ([f64; 3], [f64; 3], [f64; 3]) {
+        let r = self.reading;
+        ([r[0], r[1], r[2]], [r[3], r[4], r[5]], [r[6], r[7], r[8]])
+    }
+
+    /// Forgets the accumulated drift, as if the unit had been realigned.
+    pub fn align(&mut self) {
+        self.bias = [0.0; 9];
+    }
+
+    /// The `i`th double of the struct the guest sees. All of them are
+    /// read only.
+    pub fn register(&self, i: usize) -> f64 {
+        self.reading[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSITION: [f64; 3] = [1.0e11, -2.0e10, 3.0e9];
+    const VELOCITY: [f64; 3] = [1.0e4, 2.0e4, -3.0e3];
+    const ORIENTATION: [f64; 3] = [0.1, 0.2, 0.3];
+
+    /// The furthest any reading is from the truth.
+    fn error(irs: &Irs) -> f64 {
+        let (p, v, o) = irs.reading();
+        let reading = [p, v, o].concat();
+        let truth = [POSITION, VELOCITY, ORIENTATION].concat();
+        reading
+            .iter()
+            .zip(truth)
+            .map(|(r, t)| (r - t).abs())
+            .fold(0.0, f64::max)
+    }
+
+    fn noisy() -> IrsModel {
+        IrsModel {
+            position_noise: 10.0,
+            velocity_noise: 10.0,
+            orientation_noise: 10.0,
+            ..IrsModel::default()
+        }
+    }
+
+    fn drifting() -> IrsModel {
+        IrsModel {
+            position_drift: 1.0,
+            velocity_drift: 1.0,
+            orientation_drift: 1.0,
+            ..IrsModel::default()
+        }
+    }
+
+    #[test]
+    fn a_perfect_unit_reads_the_truth() {
+        let mut irs = Irs::new(IrsModel::default(), 1);
+        irs.update(POSITION, VELOCITY, ORIENTATION);
+        assert_eq!(irs.reading(), (POSITION, VELOCITY, ORIENTATION));
+        assert_eq!(irs.register(4), VELOCITY[1]);
+    }
+
+    #[test]
+    fn readings_are_repeatable_from_the_seed() {
+        let (mut a, mut b) = (Irs::new(noisy(), 9), Irs::new(noisy(), 9));
+        for _ in 0..10 {
+            a.update(POSITION, VELOCITY, ORIENTATION);
+            b.update(POSITION, VELOCITY, ORIENTATION);
+            assert_eq!(a.reading(), b.reading());
+        }
+        let mut c = Irs::new(noisy(), 10);
+        c.update(POSITION, VELOCITY, ORIENTATION);
+        assert_ne!(a.reading(), c.reading());
+    }
+
+    #[test]
+    fn noise_doesnt_build_up() {
+        let mut irs = Irs::new(noisy(), 3);
+        for _ in 0..10000 {
+            irs.update(POSITION, VELOCITY, ORIENTATION);
+            // Six standard deviations. The seed is fixed, so this is the
+            // same 10000 readings every run.
+            assert!(error(&irs) < 60.0);
+        }
+    }
+
+    #[test]
+    fn drift_grows_over_time() {
+        // A random walk spreads out as the square root of its steps, so
+        // across many units the error after 400 updates is around 20
+        // times the error after 1.
+        let spread = |updates: usize| {
+            let total: f64 = (0..100)
+                .map(|seed| {
+                    let mut irs = Irs::new(drifting(), seed);
+                    for _ in 0..updates {
+                        irs.update(POSITION, VELOCITY, ORIENTATION);
+                    }
+                    error(&irs)
+                })
+                .sum();
+            total / 100.0
+        };
+        let (early, late) = (spread(1), spread(400));
+        assert!(late > 10.0 * early, "{} then {}", early, late);
+
+        let mut irs = Irs::new(drifting(), 5);
+        for _ in 0..400 {
+            irs.update(POSITION, VELOCITY, ORIENTATION);
+        }
+        irs.align();
+        irs.update(POSITION, VELOCITY, ORIENTATION);
+        assert!(error(&irs) < 6.0);
+    }
+}
@@ -0,0 +1,141 @@
+// The ship's devices, memory mapped into MMIO. Each one has a fixed offset
+// into the region and a packed struct of doubles the guest sees through a
+// linker symbol. The devices are mounted on the bus themselves, so every
+// load and store the guest makes in MMIO is answered by the device as it
+// happens.
+//
+// The ship's axes are x forward, y left and z up. Its orientation is pitch,
+// roll and yaw: turned by yaw about z, then pitched nose up, then rolled
+// about its nose.
+
+use rvemu::cpu;
+use rvemu::devices::Device;
+use rvemu::exception::Exception;
+
+pub mod adf;
+pub mod chrono;
+pub mod fuel;
+pub mod irs;
+mod rng;
+pub mod thrusters;
+
+pub use adf::{Adf, AdfChannel, Beacon};
+pub use chrono::Chrono;
+pub use fuel::{Burn, Fuel};
+pub use irs::{Irs, IrsModel};
+pub use thrusters::{Thrust, Thruster, Thrusters};
+
+/// Where each device starts in MMIO, by the symbol the guest sees it as.
+pub const DEVICE_SYMBOLS: &[(&str, u64)] = &[
+    ("chrono", chrono::OFFSET),
+    ("thrusters", thrusters::OFFSET),
+    ("irs", irs::OFFSET),
+    ("adf", adf::OFFSET),
+    ("fuel", fuel::OFFSET),
+];
+
+/// Bytes of MMIO the devices take up.
+pub const MMIO_USED: u64 = fuel::OFFSET + fuel::SIZE;
+
+/// The device, by its offset, and which of its doubles the access `size`
+/// bits at `offset` into MMIO is to. Only aligned doublewords inside a
+/// device are registers.
+fn register(offset: u64, size: u8) -> Option<(u64, usize)> {
+    if size != cpu::DOUBLEWORD || !offset.is_multiple_of(8) {
+        return None;
+    }
+    [
+        (chrono::OFFSET, chrono::SIZE),
+        (thrusters::OFFSET, thrusters::SIZE),
+        (irs::OFFSET, irs::SIZE),
+        (adf::OFFSET, adf::SIZE),
+        (fuel::OFFSET, fuel::SIZE),
+    ]
+    .into_iter()
+    .find(|&(base, size)| (base..base + size).contains(&offset))
+    .map(|(base, _)| (base, ((offset - base) / 8) as usize))
+}
+
+/// The ship's x, y and z axes in the simulation's frame.
+pub fn ship_axes(orientation: [f64; 3]) -> [[f64; 3]; 3] {
+    let [pitch, roll, yaw] = orientation;
+    let (sp, cp) = pitch.sin_cos();
+    let (sr, cr) = roll.sin_cos();
+    let (sy, cy) = yaw.sin_cos();
+    let forward = [cp * cy, cp * sy, sp];
+    let left = [-sy, cy, 0.0];
+    let up = [-sp * cy, -sp * sy, cp];
+    [
+        forward,
+        [0, 1, 2].map(|i| cr * left[i] + sr * up[i]),
+        [0, 1, 2].map(|i| cr * up[i] - sr * left[i]),
+    ]
+}
+
+/// Turns `v`, along the ship's axes, into the simulation's frame.
+pub fn to_inertial(orientation: [f64; 3], v: [f64; 3]) -> [f64; 3] {
+    let axes = ship_axes(orientation);
+    [0, 1, 2].map(|i| axes[0][i] * v[0] + axes[1][i] * v[1] + axes[2][i] * v[2])
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Devices {
+    pub chrono: Chrono,
+    pub thrusters: Thrusters,
+    pub irs: Irs,
+    pub adf: Adf,
+    pub fuel: Fuel,
+}
+
+impl Devices {
+    pub fn new() -> Devices {
+        Devices::default()
+    }
+
+    /// Fires the thrusters at their throttles for `dt` seconds. If the tank
+    /// runs dry they're cut.
+    pub fn burn(&mut self, dt: f64) -> Burn {
+        let burn = self.fuel.burn(&self.thrusters.thrust(), dt);
+        if burn.duration < dt {
+            self.thrusters.cut();
+        }
+        burn
+    }
+}
+
+/// Mounted at the start of MMIO. Every register is a double, so anything
+/// but an aligned doubleword load or store faults, as does an access
+/// between devices or a store to a register the guest can only read.
+impl Device for Devices {
+    fn size(&self) -> u64 {
+        MMIO_USED
+    }
+
+    fn read(&self, offset: u64, size: u8) -> Result<u64, Exception> {
+        let (device, i) = register(offset, size).ok_or(Exception::LoadAccessFault)?;
+        let value = match device {
+            chrono::OFFSET => self.chrono.register(i),
+            thrusters::OFFSET => self.thrusters.register(i),
+            irs::OFFSET => self.irs.register(i),
+            adf::OFFSET => self.adf.register(i),
+            fuel::OFFSET => self.fuel.register(i),
+            _ => unreachable!("{:#x} isn't a device", device),
+        };
+        Ok(value.to_bits())
+    }
+
+    fn write(&mut self, offset: u64, value: u64, size: u8) -> Result<(), Exception> {
+        let (device, i) = register(offset, size).ok_or(Exception::StoreAMOAccessFault)?;
+        let value = f64::from_bits(value);
+        let written = match device {
+            thrusters::OFFSET => self.thrusters.set_register(i, value),
+            adf::OFFSET => self.adf.set_register(i, value),
+            _ => false,
+        };
+        if written {
+            Ok(())
+        } else {
+            Err(Exception::StoreAMOAccessFault)
+        }
+    }
+}
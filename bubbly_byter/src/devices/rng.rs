@@ -0,0 +1,87 @@
+// Sensor noise. Small and seeded, so a run can be replayed exactly.
+
+/// xorshift64*.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // Zero is a fixed point of xorshift.
+        Rng {
+            state: if seed == 0 {
+                0x9e37_79b9_7f4a_7c15
+            } else {
+                seed
+            },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in `(0, 1]`.
+    pub fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Normally distributed with mean 0 and standard deviation 1, by
+    /// Box-Muller.
+    pub fn gaussian(&mut self) -> f64 {
+        let (u, v) = (self.next_f64(), self.next_f64());
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_gives_the_same_numbers() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let (mut a, mut b) = (Rng::new(42), Rng::new(43));
+        assert!((0..100).any(|_| a.next_u64() != b.next_u64()));
+    }
+
+    #[test]
+    fn a_zero_seed_isnt_stuck() {
+        let mut rng = Rng::new(0);
+        assert_ne!(rng.next_u64(), 0);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+
+    #[test]
+    fn uniform_stays_in_range() {
+        let mut rng = Rng::new(7);
+        for _ in 0..10000 {
+            let u = rng.next_f64();
+            assert!(u > 0.0 && u <= 1.0);
+        }
+    }
+
+    #[test]
+    fn gaussian_has_unit_deviation() {
+        let mut rng = Rng::new(7);
+        let samples: Vec<f64> = (0..10000).map(|_| rng.gaussian()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.05, "mean {}", mean);
+        assert!((var.sqrt() - 1.0).abs() < 0.05, "deviation {}", var.sqrt());
+        assert!(samples.iter().all(|x| x.is_finite() && x.abs() < 6.0));
+    }
+}
@@ -0,0 +1,112 @@
+// `thrusters`: throttles the guest sets and the specs of the engines
+// behind them.
+//
+// struct thruster { double throttle; double max_mass_flow_rate; double specific_impulse; }
+// struct { struct thruster attitude[3]; struct thruster position[3]; }
+
+pub const OFFSET: u64 = 0x100;
+pub const THRUSTER_SIZE: u64 = 3 * 8;
+pub const N_THRUSTERS: usize = 6;
+pub const SIZE: u64 = N_THRUSTERS as u64 * THRUSTER_SIZE;
+
+/// g0, for turning specific impulse into exhaust velocity. m s^-2.
+pub const STANDARD_GRAVITY: f64 = 9.80665;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Thruster {
+    /// 0 to 1. The only field the guest can change.
+    pub throttle: f64,
+    /// kg s^-1 at full throttle.
+    pub max_mass_flow_rate: f64,
+    /// Seconds.
+    pub specific_impulse: f64,
+}
+
+impl Thruster {
+    pub fn new(max_mass_flow_rate: f64, specific_impulse: f64) -> Thruster {
+        Thruster {
+            throttle: 0.0,
+            max_mass_flow_rate,
+            specific_impulse,
+        }
+    }
+
+    /// Propellant burnt at the current throttle, kg s^-1.
+    pub fn mass_flow_rate(&self) -> f64 {
+        self.throttle * self.max_mass_flow_rate
+    }
+
+    /// Newtons at the current throttle.
+    pub fn thrust(&self) -> f64 {
+        self.mass_flow_rate() * self.specific_impulse * STANDARD_GRAVITY
+    }
+}
+
+/// What the thrusters are doing, for the physics step.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Thrust {
+    /// Newtons along the ship's x, y and z axes.
+    pub force: [f64; 3],
+    /// Newtons from the pitch, roll and yaw thrusters.
+    pub attitude: [f64; 3],
+    /// Propellant burnt by all of them, kg s^-1.
+    pub mass_flow_rate: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Thrusters {
+    /// Pitch, roll and yaw.
+    pub attitude: [Thruster; 3],
+    /// x, y and z.
+    pub position: [Thruster; 3],
+}
+
+impl Thrusters {
+    fn all(&self) -> impl Iterator<Item = &Thruster> {
+        self.attitude.iter().chain(&self.position)
+    }
+
+    fn all_mut(&mut self) -> impl Iterator<Item = &mut Thruster> {
+        self.attitude.iter_mut().chain(&mut self.position)
+    }
+
+    pub fn thrust(&self) -> Thrust {
+        Thrust {
+            force: [0, 1, 2].map(|i| self.position[i].thrust()),
+            attitude: [0, 1, 2].map(|i| self.attitude[i].thrust()),
+            mass_flow_rate: self.all().map(Thruster::mass_flow_rate).sum(),
+        }
+    }
+
+    /// Shuts every thruster off.
+    pub fn cut(&mut self) {
+        for t in self.all_mut() {
+            t.throttle = 0.0;
+        }
+    }
+
+    /// The `i`th double of the struct the guest sees.
+    pub fn register(&self, i: usize) -> f64 {
+        let t = self.all().nth(i / 3).expect("register is inside thrusters");
+        [t.throttle, t.max_mass_flow_rate, t.specific_impulse][i % 3]
+    }
+
+    /// The guest setting the `i`th double. Only the throttles can be set:
+    /// anything outside 0 to 1 is clamped and NaN is taken as off. Returns
+    /// whether the register is writable.
+    pub fn set_register(&mut self, i: usize, value: f64) -> bool {
+        if !i.is_multiple_of(3) {
+            return false;
+        }
+        let t = self
+            .all_mut()
+            .nth(i / 3)
+            .expect("register is inside thrusters");
+        t.throttle = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+        true
+    }
+}
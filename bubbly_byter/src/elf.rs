@@ -0,0 +1,269 @@
+// Just enough of an ELF64 reader to pull the loadable segments and entry
+// point out of a statically linked RISC-V executable.
+
+use std::convert::TryInto;
+use std::io;
+
+const EM_RISCV: u16 = 243;
+const ET_EXEC: u16 = 2;
+const PT_LOAD: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+const SHDR_SIZE: usize = 64;
+const SYM_SIZE: usize = 24;
+
+#[derive(Debug)]
+pub enum ElfError {
+    Io(io::Error),
+    /// Not a little-endian, 64-bit RISC-V executable.
+    NotRiscv64,
+    /// A header or segment runs past the end of the file.
+    Truncated,
+    /// A segment claims more file bytes than it has memory.
+    BadSegment {
+        index: usize,
+    },
+    /// A segment with contents would land somewhere other than RAM.
+    OutsideRam {
+        index: usize,
+        vaddr: u64,
+        memsz: u64,
+    },
+    /// The loadable segments add up to more than the machine's RAM.
+    TooLarge {
+        size: u64,
+        capacity: u64,
+    },
+    EntryOutsideRam {
+        entry: u64,
+    },
+}
+
+impl From<io::Error> for ElfError {
+    fn from(e: io::Error) -> Self {
+        ElfError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub index: usize,
+    pub vaddr: u64,
+    pub memsz: u64,
+    /// The first `data.len()` bytes of the segment; the rest is zero.
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElfImage {
+    pub entry: u64,
+    pub segments: Vec<Segment>,
+    /// Named symbols and their values, from `.symtab`. Empty for a
+    /// stripped file.
+    pub symbols: Vec<(String, u64)>,
+}
+
+/// The bytes of table entry `index`, from a table at `offset` with entries
+/// `size` bytes apart, as far as the end of the file.
+fn table_entry(b: &[u8], offset: usize, index: usize, size: usize) -> Result<&[u8], ElfError> {
+    index
+        .checked_mul(size)
+        .and_then(|at| at.checked_add(offset))
+        .and_then(|at| b.get(at..))
+        .ok_or(ElfError::Truncated)
+}
+
+fn u16_at(b: &[u8], at: usize) -> Result<u16, ElfError> {
+    let bytes = at
+        .checked_add(2)
+        .and_then(|end| b.get(at..end))
+        .ok_or(ElfError::Truncated)?;
+    Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn u32_at(b: &[u8], at: usize) -> Result<u32, ElfError> {
+    let bytes = at
+        .checked_add(4)
+        .and_then(|end| b.get(at..end))
+        .ok_or(ElfError::Truncated)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn u64_at(b: &[u8], at: usize) -> Result<u64, ElfError> {
+    let bytes = at
+        .checked_add(8)
+        .and_then(|end| b.get(at..end))
+        .ok_or(ElfError::Truncated)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+impl ElfImage {
+    /// Reads the `PT_LOAD` segments of an ELF file. Empty segments are
+    /// dropped.
+    pub fn parse(b: &[u8]) -> Result<ElfImage, ElfError> {
+        if b.len() < EHDR_SIZE {
+            return Err(ElfError::Truncated);
+        }
+        // ELFCLASS64, ELFDATA2LSB
+        if b[0..4] != *b"\x7fELF" || b[4] != 2 || b[5] != 1 {
+            return Err(ElfError::NotRiscv64);
+        }
+        if u16_at(b, 16)? != ET_EXEC || u16_at(b, 18)? != EM_RISCV {
+            return Err(ElfError::NotRiscv64);
+        }
+
+        let entry = u64_at(b, 24)?;
+        let phoff = u64_at(b, 32)? as usize;
+        let phentsize = u16_at(b, 54)? as usize;
+        let phnum = u16_at(b, 56)? as usize;
+        if phnum > 0 && phentsize < PHDR_SIZE {
+            return Err(ElfError::Truncated);
+        }
+
+        let mut segments = vec![];
+        for index in 0..phnum {
+            let ph = table_entry(b, phoff, index, phentsize)?;
+            if u32_at(ph, 0)? != PT_LOAD {
+                continue;
+            }
+            let offset = u64_at(ph, 8)?;
+            let vaddr = u64_at(ph, 16)?;
+            let filesz = u64_at(ph, 32)?;
+            let memsz = u64_at(ph, 40)?;
+            if filesz > memsz {
+                return Err(ElfError::BadSegment { index });
+            }
+            if memsz == 0 {
+                continue;
+            }
+            let data = offset
+                .checked_add(filesz)
+                .and_then(|end| b.get(offset as usize..end as usize))
+                .ok_or(ElfError::Truncated)?;
+            segments.push(Segment {
+                index,
+                vaddr,
+                memsz,
+                data: data.to_vec(),
+            });
+        }
+
+        let symbols = symbols(b)?;
+
+        Ok(ElfImage {
+            entry,
+            segments,
+            symbols,
+        })
+    }
+}
+
+/// Reads every named symbol out of the first `SHT_SYMTAB` section.
+fn symbols(b: &[u8]) -> Result<Vec<(String, u64)>, ElfError> {
+    let shoff = u64_at(b, 40)? as usize;
+    let shentsize = u16_at(b, 58)? as usize;
+    let shnum = u16_at(b, 60)? as usize;
+    if shnum == 0 {
+        return Ok(vec![]);
+    }
+    if shentsize < SHDR_SIZE {
+        return Err(ElfError::Truncated);
+    }
+
+    let section = |i: usize| -> Result<(u32, usize, usize, usize), ElfError> {
+        let sh = table_entry(b, shoff, i, shentsize)?;
+        Ok((
+            u32_at(sh, 4)?,
+            u64_at(sh, 24)? as usize,
+            u64_at(sh, 32)? as usize,
+            u32_at(sh, 40)? as usize,
+        ))
+    };
+
+    let mut symbols = vec![];
+    for i in 0..shnum {
+        let (sh_type, offset, size, link) = section(i)?;
+        if sh_type != SHT_SYMTAB {
+            continue;
+        }
+        let (_, strtab, strtab_size, _) = section(link)?;
+        let strings = strtab
+            .checked_add(strtab_size)
+            .and_then(|end| b.get(strtab..end))
+            .ok_or(ElfError::Truncated)?;
+        let table = offset
+            .checked_add(size)
+            .and_then(|end| b.get(offset..end))
+            .ok_or(ElfError::Truncated)?;
+
+        for sym in table.chunks(SYM_SIZE) {
+            let name = u32_at(sym, 0)? as usize;
+            let value = u64_at(sym, 8)?;
+            let name = strings.get(name..).ok_or(ElfError::Truncated)?;
+            let name = &name[..name.iter().position(|c| *c == 0).unwrap_or(name.len())];
+            if !name.is_empty() {
+                symbols.push((String::from_utf8_lossy(name).into_owned(), value));
+            }
+        }
+        break;
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An ELF header with no segments or sections, for tests to fill in.
+    fn header() -> Vec<u8> {
+        let mut b = vec![0; EHDR_SIZE];
+        b[0..4].copy_from_slice(b"\x7fELF");
+        b[4] = 2;
+        b[5] = 1;
+        b[16..18].copy_from_slice(&ET_EXEC.to_le_bytes());
+        b[18..20].copy_from_slice(&EM_RISCV.to_le_bytes());
+        b
+    }
+
+    #[test]
+    fn empty_executable_parses() {
+        let image = ElfImage::parse(&header()).unwrap();
+        assert!(image.segments.is_empty());
+        assert!(image.symbols.is_empty());
+    }
+
+    #[test]
+    fn huge_program_header_offset_is_truncated() {
+        let mut b = header();
+        b[32..40].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+        b[54..56].copy_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        b[56..58].copy_from_slice(&2u16.to_le_bytes());
+        assert!(matches!(ElfImage::parse(&b), Err(ElfError::Truncated)));
+    }
+
+    #[test]
+    fn huge_section_header_offset_is_truncated() {
+        let mut b = header();
+        b[40..48].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+        b[58..60].copy_from_slice(&(SHDR_SIZE as u16).to_le_bytes());
+        b[60..62].copy_from_slice(&2u16.to_le_bytes());
+        assert!(matches!(ElfImage::parse(&b), Err(ElfError::Truncated)));
+    }
+
+    #[test]
+    fn segment_past_the_end_of_the_file_is_truncated() {
+        let mut b = header();
+        b[32..40].copy_from_slice(&(EHDR_SIZE as u64).to_le_bytes());
+        b[54..56].copy_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        b[56..58].copy_from_slice(&1u16.to_le_bytes());
+        let mut ph = vec![0; PHDR_SIZE];
+        ph[0..4].copy_from_slice(&PT_LOAD.to_le_bytes());
+        ph[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        ph[32..40].copy_from_slice(&16u64.to_le_bytes());
+        ph[40..48].copy_from_slice(&16u64.to_le_bytes());
+        b.extend(ph);
+        assert!(matches!(ElfImage::parse(&b), Err(ElfError::Truncated)));
+    }
+}
@@ -1,2 +1,9 @@
 pub mod base_system;
+pub mod celestial;
+pub mod cost;
+pub mod devices;
+pub mod elf;
+pub mod math;
+pub mod memory;
+pub mod printf;
 pub mod spf;
@@ -1,10 +1,49 @@
-use rvemu::exception::Exception;
+use std::path::Path;
+use std::process;
 
-use bubbly_byter::base_system::BubblyByter;
+use bubbly_byter::base_system::{BubblyByter, NvramError};
+use bubbly_byter::cost::CostTable;
+use bubbly_byter::devices::{Fuel, Thruster};
+
+fn main() -> Result<(), NvramError> {
+    let nvram = Path::new("nvram.img");
 
-fn main() -> Result<(), Exception> {
     let mut sys = BubblyByter::new();
-    sys.load_kernel("../bubbly_byter_cc/build/kernel.img");
+    match CostTable::load("spf_costs.conf") {
+        Ok(costs) => sys.set_cost_table(costs),
+        Err(e) => eprintln!("using default SPF costs: {:?}", e),
+    }
+    if let Err(e) = sys.load_elf("../bubbly_byter_cc/build/kernel.elf") {
+        eprintln!("couldn't load kernel.elf: {:?}", e);
+        process::exit(1);
+    }
+    if nvram.exists() {
+        sys.restore_nvram_from_file(nvram)?;
+    }
+
+    {
+        let mut devices = sys.devices_mut();
+        // Chuckle Chargers all round
+        devices.thrusters.attitude = std::array::from_fn(|_| Thruster::new(0.78, 331.4361));
+        devices.thrusters.position = std::array::from_fn(|_| Thruster::new(0.78, 331.4361));
+        devices.fuel = Fuel {
+            propellant: 500e3,
+            capacity: 600e3,
+            dry_mass: 1520e3,
+            cargo_mass: 10e3,
+            max_cargo_mass: 35e3,
+        };
+    }
+
+    println!("{:?}", sys.execute_budget(2000));
+    println!("{:?}", sys.devices_mut().burn(1.0));
+    let (log, dropped) = sys.drain_log();
+    for message in log {
+        println!("debuglog: {}", message);
+    }
+    if dropped > 0 {
+        println!("debuglog: {} messages dropped", dropped);
+    }
 
-    sys.execute(2000)
+    sys.save_nvram_to_file(nvram)
 }
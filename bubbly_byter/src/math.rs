@@ -0,0 +1,313 @@
+// Host implementations of the system provided functions that are more
+// than a call into std.
+
+pub fn add3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale3(a: [f64; 3], s: f64) -> [f64; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub fn dot3(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn norm3(a: [f64; 3]) -> f64 {
+    dot3(a, a).sqrt()
+}
+
+/// `i` to the power `p`, modulo `m`, as a value in `[0, |m|)`.
+///
+/// Follows RISC-V's `rem`, where anything modulo zero is itself: with
+/// `m == 0` this is `i` to the `p`, wrapping on overflow. A negative `p`
+/// gives 0.
+pub fn modpow(i: i64, p: i64, m: i64) -> i64 {
+    if p < 0 {
+        return 0;
+    }
+    let mut p = p as u64;
+    if m == 0 {
+        let (mut base, mut result) = (i, 1i64);
+        while p > 0 {
+            if p & 1 == 1 {
+                result = result.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+            p >>= 1;
+        }
+        return result;
+    }
+
+    let m = m.unsigned_abs() as u128;
+    let mut base = (i as i128).rem_euclid(m as i128) as u128;
+    let mut result = 1 % m;
+    while p > 0 {
+        if p & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        p >>= 1;
+    }
+    result as i64
+}
+
+/// Quotient and remainder of `i / m`, truncating toward zero like C.
+///
+/// Division by zero and overflow follow RISC-V's `div`/`rem` rather than
+/// trapping: `i / 0` is `(-1, i)` and `i64::MIN / -1` is `(i64::MIN, 0)`.
+pub fn divmod(i: i64, m: i64) -> (i64, i64) {
+    if m == 0 {
+        (-1, i)
+    } else {
+        (i.wrapping_div(m), i.wrapping_rem(m))
+    }
+}
+
+/// Below this, `erf` is summed from its series. Above it, `erfc` comes
+/// from its continued fraction, which converges quickly out there.
+const ERF_SERIES_MAX: f64 = 2.5;
+
+/// erf(x) = 2/sqrt(pi) exp(-x^2) sum 2^n x^(2n+1) / (1 3 5 ... (2n+1)).
+/// Every term is positive, so there's no cancellation to lose digits to.
+fn erf_series(x: f64) -> f64 {
+    let x2 = x * x;
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 0.0;
+    while term > sum * f64::EPSILON {
+        n += 1.0;
+        term *= 2.0 * x2 / (2.0 * n + 1.0);
+        sum += term;
+    }
+    std::f64::consts::FRAC_2_SQRT_PI * (-x2).exp() * sum
+}
+
+/// erfc(x) = exp(-x^2)/sqrt(pi) / (x + (1/2)/(x + 1/(x + (3/2)/(x + ...)))),
+/// evaluated from the tail, for `x >= ERF_SERIES_MAX`.
+fn erfc_continued_fraction(x: f64) -> f64 {
+    let mut f = x;
+    for k in (1..=80).rev() {
+        f = x + (k as f64 / 2.0) / f;
+    }
+    (-x * x).exp() / std::f64::consts::PI.sqrt() / f
+}
+
+pub fn erf(x: f64) -> f64 {
+    let a = x.abs();
+    let r = if a.is_nan() {
+        return x;
+    } else if a < ERF_SERIES_MAX {
+        erf_series(a)
+    } else {
+        1.0 - erfc_continued_fraction(a)
+    };
+    r.copysign(x)
+}
+
+pub fn erfc(x: f64) -> f64 {
+    if x.is_nan() {
+        x
+    } else if x < 0.0 {
+        2.0 - erfc(-x)
+    } else if x < ERF_SERIES_MAX {
+        1.0 - erf_series(x)
+    } else {
+        erfc_continued_fraction(x)
+    }
+}
+
+/// `c[0] + c[1] x + c[2] x^2 + ...`
+pub fn horner(c: &[f64], x: f64) -> f64 {
+    c.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// Real roots of `a x^2 + b x + c`, smallest first. Falls back to the
+/// linear root when `a` is zero. No roots are reported when every `x` is
+/// a root.
+pub fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return if b == 0.0 { vec![] } else { vec![-c / b] };
+    }
+    let disc = b * b - 4.0 * a * c;
+    if disc < 0.0 || disc.is_nan() {
+        vec![]
+    } else if disc == 0.0 {
+        vec![-b / (2.0 * a)]
+    } else {
+        // Avoids subtracting nearly equal numbers when b^2 >> 4ac.
+        let q = -0.5 * (b + disc.sqrt().copysign(b));
+        let (r1, r2) = (q / a, c / q);
+        vec![r1.min(r2), r1.max(r2)]
+    }
+}
+
+/// Solves Kepler's equation `M = E - e sin E` for the eccentric anomaly
+/// `E` of an elliptical orbit, `0 <= e < 1`. `E` is in the same
+/// revolution as `M`. Anything else is NaN.
+pub fn kepler(m: f64, e: f64) -> f64 {
+    use std::f64::consts::{PI, TAU};
+
+    if !(0.0..1.0).contains(&e) || !m.is_finite() {
+        return f64::NAN;
+    }
+
+    // Solve in (-pi, pi] and shift back.
+    let revolutions = (m / TAU).round() * TAU;
+    let m_wrapped = m - revolutions;
+
+    // Danby's starting guess; Newton converges from it for every e < 1.
+    let mut ea = m_wrapped + 0.85 * e * m_wrapped.sin().signum();
+    if m_wrapped == 0.0 {
+        ea = 0.0;
+    }
+    for _ in 0..50 {
+        let step = (ea - e * ea.sin() - m_wrapped) / (1.0 - e * ea.cos());
+        ea -= step;
+        if step.abs() <= 4.0 * f64::EPSILON * ea.abs().max(PI) {
+            break;
+        }
+    }
+    ea + revolutions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(got: f64, want: f64) {
+        assert!(
+            (got - want).abs() <= 4.0 * f64::EPSILON * want.abs(),
+            "{} != {}",
+            got,
+            want
+        );
+    }
+
+    #[test]
+    fn modpow_reduces_into_the_modulus() {
+        assert_eq!(modpow(3, 4, 5), 1);
+        assert_eq!(modpow(-2, 3, 5), 2);
+        assert_eq!(modpow(7, 0, 1), 0);
+        assert_eq!(modpow(2, -1, 5), 0);
+        assert_eq!(modpow(i64::MAX, i64::MAX, i64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn modpow_takes_the_size_of_a_negative_modulus() {
+        assert_eq!(modpow(3, 4, -5), 1);
+        assert_eq!(modpow(-2, 3, -5), 2);
+        assert_eq!(modpow(5, 3, i64::MIN), 125);
+    }
+
+    #[test]
+    fn modpow_by_zero_wraps_without_capping_the_exponent() {
+        assert_eq!(modpow(3, 40, 0), 3i64.wrapping_pow(40));
+        assert_eq!(modpow(-1, 3, 0), -1);
+        // 3^(2^32 + 1) mod 2^64, past where an exponent that fit in a u32
+        // would stop.
+        assert_eq!(modpow(3, (1 << 32) + 1, 0), 7473929035676909571);
+    }
+
+    #[test]
+    fn divmod_truncates_toward_zero() {
+        assert_eq!(divmod(7, 2), (3, 1));
+        assert_eq!(divmod(-7, 2), (-3, -1));
+        assert_eq!(divmod(7, -2), (-3, 1));
+    }
+
+    #[test]
+    fn divmod_follows_risc_v_where_c_is_undefined() {
+        assert_eq!(divmod(7, 0), (-1, 7));
+        assert_eq!(divmod(i64::MIN, -1), (i64::MIN, 0));
+    }
+
+    #[test]
+    fn erf_matches_known_values() {
+        assert_close(erf(0.1), 0.1124629160182849);
+        assert_close(erf(0.5), 0.5204998778130465);
+        assert_close(erf(1.0), 0.8427007929497149);
+        assert_close(erf(-1.0), -0.8427007929497149);
+        assert_close(erf(3.0), 0.9999779095030014);
+        assert_eq!(erf(0.0), 0.0);
+        assert_eq!(erf(f64::INFINITY), 1.0);
+        assert!(erf(f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn erfc_matches_known_values() {
+        assert_close(erfc(0.5), 0.4795001221869535);
+        assert_close(erfc(1.0), 0.15729920705028513);
+        assert_close(erfc(-1.0), 1.842700792949715);
+        // Out where 1 - erf(x) would have cancelled to nothing.
+        assert_close(erfc(3.0), 2.2090496998585438e-05);
+        assert_close(erfc(6.0), 2.1519736712498916e-17);
+        assert!(erfc(f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn quadratic_roots_come_smallest_first() {
+        assert_eq!(quadratic_roots(1.0, -3.0, 2.0), vec![1.0, 2.0]);
+        assert_eq!(quadratic_roots(-1.0, 3.0, -2.0), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn quadratic_roots_of_a_line() {
+        assert_eq!(quadratic_roots(0.0, 2.0, 4.0), vec![-2.0]);
+        assert_eq!(quadratic_roots(0.0, 0.0, 4.0), vec![]);
+        assert_eq!(quadratic_roots(0.0, 0.0, 0.0), vec![]);
+    }
+
+    #[test]
+    fn quadratic_double_root_is_reported_once() {
+        assert_eq!(quadratic_roots(1.0, -2.0, 1.0), vec![1.0]);
+    }
+
+    #[test]
+    fn quadratic_complex_roots_are_not_reported() {
+        assert_eq!(quadratic_roots(1.0, 0.0, 1.0), vec![]);
+        assert_eq!(quadratic_roots(1.0, f64::NAN, 1.0), vec![]);
+    }
+
+    #[test]
+    fn kepler_solves_keplers_equation() {
+        for &e in &[0.0, 1e-9, 0.1, 0.5, 0.9, 0.99, 0.999999] {
+            for &m in &[-7.0, -3.0, -0.5, 1e-6, 0.25, 1.0, 3.0, 3.1, 10.0] {
+                let ea = kepler(m, e);
+                assert!(
+                    (ea - e * ea.sin() - m).abs() <= 1e-12,
+                    "e {} M {} gave E {}",
+                    e,
+                    m,
+                    ea
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn kepler_of_a_circle_is_the_mean_anomaly() {
+        assert_eq!(kepler(0.0, 0.0), 0.0);
+        assert_close(kepler(1.0, 0.0), 1.0);
+        assert_close(kepler(10.0, 0.0), 10.0);
+    }
+
+    #[test]
+    fn kepler_outside_an_ellipse_is_nan() {
+        assert!(kepler(1.0, 1.0).is_nan());
+        assert!(kepler(1.0, -0.1).is_nan());
+        assert!(kepler(f64::INFINITY, 0.5).is_nan());
+    }
+}
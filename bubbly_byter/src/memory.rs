@@ -0,0 +1,396 @@
+// The Bubbly Byter's address space. The emulator mounts its devices from
+// this and `gen_memmap` writes the linker script MEMORY block and C header
+// for `bubbly_byter_cc` from it, so the two can't drift apart.
+
+use rvemu::cpu;
+use rvemu::devices::dram::Dram;
+use rvemu::devices::Device;
+use rvemu::exception::Exception;
+use std::sync::{Arc, Mutex};
+
+use crate::celestial::{CELOBJDAT_SIZE, MAX_CELESTIALS};
+use crate::devices::{adf, DEVICE_SYMBOLS};
+
+/// Offset into ROM of the system provided function table. The first
+/// 0x400 bytes of ROM are reserved.
+pub const SPF_TABLE_OFFSET: u64 = 0x400;
+/// Offset into ROM of the celestial table, leaving the system provided
+/// function table room to grow.
+pub const CELESTIALS_OFFSET: u64 = 0x1000;
+
+/// Copies `len` bytes out of `device` starting at `offset` into it.
+pub fn read_bytes<D: Device + ?Sized>(
+    device: &D,
+    offset: u64,
+    len: u64,
+) -> Result<Vec<u8>, Exception> {
+    (offset..offset + len)
+        .map(|addr| device.read(addr, cpu::BYTE).map(|b| b as u8))
+        .collect()
+}
+
+pub fn write_bytes<D: Device + ?Sized>(
+    device: &mut D,
+    offset: u64,
+    bytes: &[u8],
+) -> Result<(), Exception> {
+    for (addr, b) in (offset..).zip(bytes) {
+        device.write(addr, *b as u64, cpu::BYTE)?;
+    }
+    Ok(())
+}
+
+/// Memory the guest can only read; a store to it faults. The host fills it
+/// in through `dram`.
+#[derive(Debug)]
+pub struct Rom {
+    pub dram: Dram,
+}
+
+impl Rom {
+    pub fn new(size: u64) -> Rom {
+        Rom {
+            dram: Dram::new(size),
+        }
+    }
+}
+
+impl Device for Rom {
+    fn size(&self) -> u64 {
+        self.dram.size()
+    }
+
+    fn read(&self, offset: u64, size: u8) -> Result<u64, Exception> {
+        self.dram.read(offset, size)
+    }
+
+    fn write(&mut self, _offset: u64, _value: u64, _size: u8) -> Result<(), Exception> {
+        Err(Exception::StoreAMOAccessFault)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub base: u64,
+    pub size: u64,
+}
+
+impl MemoryRegion {
+    pub const fn new(base: u64, size: u64) -> MemoryRegion {
+        MemoryRegion { base, size }
+    }
+
+    pub fn end(&self) -> u64 {
+        self.base + self.size
+    }
+
+    pub fn contains(&self, addr: u64) -> bool {
+        self.base <= addr && addr < self.end()
+    }
+
+    /// True if all of `[addr, addr + len)` is inside the region.
+    pub fn contains_range(&self, addr: u64, len: u64) -> bool {
+        match addr.checked_add(len) {
+            Some(end) => self.base <= addr && end <= self.end(),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryMap {
+    /// Program, data and stack. Wiped on every reset.
+    pub ram: MemoryRegion,
+    /// System provided functions and the celestial table.
+    pub rom: MemoryRegion,
+    /// Ship's devices.
+    pub mmio: MemoryRegion,
+    /// Kept between ticks.
+    pub nvram: MemoryRegion,
+}
+
+impl Default for MemoryMap {
+    fn default() -> Self {
+        MemoryMap {
+            ram: MemoryRegion::new(0x4000_0000, 0x1_0000),
+            rom: MemoryRegion::new(0x8000_0000, 0x10_0000),
+            mmio: MemoryRegion::new(0x9000_0000, 0x1000),
+            nvram: MemoryRegion::new(0xa000_0000, 0x1_0000),
+        }
+    }
+}
+
+impl MemoryMap {
+    pub fn regions(&self) -> [(&'static str, MemoryRegion); 4] {
+        [
+            ("ram", self.ram),
+            ("rom", self.rom),
+            ("mmio", self.mmio),
+            ("nvram", self.nvram),
+        ]
+    }
+
+    /// Name of the region `addr` falls in, if it's mapped at all.
+    pub fn region_of(&self, addr: u64) -> Option<&'static str> {
+        self.regions()
+            .into_iter()
+            .find(|(_, r)| r.contains(addr))
+            .map(|(name, _)| name)
+    }
+
+    /// True if all of `[addr, addr + len)` is inside one region.
+    pub fn contains_range(&self, addr: u64, len: u64) -> bool {
+        self.regions()
+            .into_iter()
+            .any(|(_, r)| r.contains_range(addr, len))
+    }
+
+    /// Address of the first system provided function.
+    pub fn spf_base(&self) -> u64 {
+        self.rom.base + SPF_TABLE_OFFSET
+    }
+
+    /// Address of `celestials`, the table of `MAX_CELESTIALS` packed
+    /// `celobjdat`s.
+    pub fn celestials_base(&self) -> u64 {
+        self.rom.base + CELESTIALS_OFFSET
+    }
+
+    /// Initial stack pointer; the stack grows down from the top of RAM.
+    pub fn stack_top(&self) -> u64 {
+        self.ram.end() - 0x400
+    }
+
+    /// The MEMORY block and fixed symbols for `link.ld`. The `rom` region
+    /// starts at the system provided function table, and each device is
+    /// placed at its offset into `mmio`.
+    pub fn linker_script(&self) -> String {
+        let spf_base = self.spf_base();
+        let mut ld = String::from(
+            "/* Generated from bubbly_byter's MemoryMap by gen_memmap. Do not edit. */\n",
+        );
+        ld += "MEMORY\n{\n";
+        ld += &format!(
+            "  ram   (wxa!ri) : ORIGIN = {:#x}, LENGTH = {:#x}\n",
+            self.ram.base, self.ram.size
+        );
+        ld += &format!(
+            "  rom   (rx)     : ORIGIN = {:#x}, LENGTH = {:#x}\n",
+            spf_base,
+            self.rom.end() - spf_base
+        );
+        ld += &format!(
+            "  mmio  (rw)     : ORIGIN = {:#x}, LENGTH = {:#x}\n",
+            self.mmio.base, self.mmio.size
+        );
+        ld += &format!(
+            "  nvram (rw)     : ORIGIN = {:#x}, LENGTH = {:#x}\n",
+            self.nvram.base, self.nvram.size
+        );
+        ld += "}\n\n";
+        ld += &format!("n_bodies_count = {};\n", MAX_CELESTIALS);
+        ld += &format!("celestials = {:#x};\n", self.celestials_base());
+        for (name, offset) in DEVICE_SYMBOLS {
+            ld += &format!("{} = {:#x};\n", name, self.mmio.base + offset);
+        }
+        ld
+    }
+
+    /// `#define`s of every region's base and size, and the table sizes,
+    /// for C programs.
+    pub fn c_header(&self) -> String {
+        let mut h = String::from(
+            "// Generated from bubbly_byter's MemoryMap by gen_memmap. Do not edit.\n\n#pragma once\n\n",
+        );
+        for (name, r) in self.regions() {
+            let name = name.to_uppercase();
+            h += &format!("#define {}_BASE {:#x}\n", name, r.base);
+            h += &format!("#define {}_SIZE {:#x}\n", name, r.size);
+        }
+        h += &format!("\n#define N_CELESTIALS {}\n", MAX_CELESTIALS);
+        h += &format!("#define CELOBJDAT_SIZE {}\n", CELOBJDAT_SIZE);
+        h += &format!("#define N_ADF_CHANNELS {}\n", adf::N_CHANNELS);
+        h
+    }
+}
+
+/// A region of guest memory and the device behind it.
+#[derive(Clone)]
+struct Mapping {
+    region: MemoryRegion,
+    device: Arc<Mutex<dyn Device>>,
+    writable: bool,
+}
+
+/// Host-side access to guest memory by guest address, for system provided
+/// functions that take pointers. Cloning shares the underlying memory.
+#[derive(Clone, Default)]
+pub struct GuestMemory {
+    regions: Vec<Mapping>,
+}
+
+impl GuestMemory {
+    pub fn new() -> GuestMemory {
+        GuestMemory::default()
+    }
+
+    pub fn mount<D: Device + 'static>(&mut self, region: MemoryRegion, device: Arc<Mutex<D>>) {
+        self.regions.push(Mapping {
+            region,
+            device,
+            writable: true,
+        });
+    }
+
+    /// Mounts `device` so that system provided functions can read it but
+    /// never write through a pointer into it, whatever the guest could do.
+    pub fn mount_read_only<D: Device + 'static>(
+        &mut self,
+        region: MemoryRegion,
+        device: Arc<Mutex<D>>,
+    ) {
+        self.regions.push(Mapping {
+            region,
+            device,
+            writable: false,
+        });
+    }
+
+    /// The region holding all of `[addr, addr + len)` and its backing
+    /// device. Ranges that straddle regions are treated as unmapped.
+    fn find(&self, addr: u64, len: u64) -> Option<(u64, &Mapping)> {
+        self.regions
+            .iter()
+            .find(|m| m.region.contains_range(addr, len))
+            .map(|m| (addr - m.region.base, m))
+    }
+
+    /// Like `find`, for a write: the range has to be in a writable region.
+    fn find_writable(&self, addr: u64, len: u64) -> Result<(u64, &Mapping), Exception> {
+        self.find(addr, len)
+            .filter(|(_, m)| m.writable)
+            .ok_or(Exception::StoreAMOAccessFault)
+    }
+
+    /// Checks that all of `[addr, addr + len)` can be written, so a
+    /// function with more than one result can fault before writing any
+    /// of them.
+    pub fn check_writable(&self, addr: u64, len: u64) -> Result<(), Exception> {
+        self.find_writable(addr, len).map(|_| ())
+    }
+
+    pub fn read_bytes(&self, addr: u64, len: u64) -> Result<Vec<u8>, Exception> {
+        let (offset, m) = self.find(addr, len).ok_or(Exception::LoadAccessFault)?;
+        read_bytes(&*m.device.lock().unwrap(), offset, len)
+    }
+
+    pub fn write_bytes(&self, addr: u64, bytes: &[u8]) -> Result<(), Exception> {
+        let (offset, m) = self.find_writable(addr, bytes.len() as u64)?;
+        write_bytes(&mut *m.device.lock().unwrap(), offset, bytes)
+    }
+
+    /// Reads a NUL terminated string, without the NUL, stopping after
+    /// `max_len` bytes if there isn't one.
+    pub fn read_cstr(&self, addr: u64, max_len: u64) -> Result<Vec<u8>, Exception> {
+        let mut s = vec![];
+        for a in addr..addr.saturating_add(max_len) {
+            match self.read_bytes(a, 1)?[0] {
+                0 => break,
+                b => s.push(b),
+            }
+        }
+        Ok(s)
+    }
+
+    pub fn read_u64(&self, addr: u64) -> Result<u64, Exception> {
+        let (offset, m) = self.find(addr, 8).ok_or(Exception::LoadAccessFault)?;
+        let device = m.device.lock().unwrap();
+        device.read(offset, cpu::DOUBLEWORD)
+    }
+
+    pub fn write_u64(&self, addr: u64, value: u64) -> Result<(), Exception> {
+        let (offset, m) = self.find_writable(addr, 8)?;
+        let mut device = m.device.lock().unwrap();
+        device.write(offset, value, cpu::DOUBLEWORD)
+    }
+
+    pub fn read_f64(&self, addr: u64) -> Result<f64, Exception> {
+        self.read_u64(addr).map(f64::from_bits)
+    }
+
+    pub fn write_f64(&self, addr: u64, value: f64) -> Result<(), Exception> {
+        self.write_u64(addr, value.to_bits())
+    }
+
+    /// Reads a `double[2]`.
+    pub fn read_vec2(&self, addr: u64) -> Result<[f64; 2], Exception> {
+        Ok([self.read_f64(addr)?, self.read_f64(addr.wrapping_add(8))?])
+    }
+
+    pub fn write_vec2(&self, addr: u64, v: [f64; 2]) -> Result<(), Exception> {
+        self.check_writable(addr, 16)?;
+        self.write_f64(addr, v[0])?;
+        self.write_f64(addr.wrapping_add(8), v[1])
+    }
+
+    /// Reads a `vec3_t`, or equally a `double[3]`.
+    pub fn read_vec3(&self, addr: u64) -> Result<[f64; 3], Exception> {
+        Ok([
+            self.read_f64(addr)?,
+            self.read_f64(addr.wrapping_add(8))?,
+            self.read_f64(addr.wrapping_add(16))?,
+        ])
+    }
+
+    pub fn write_vec3(&self, addr: u64, v: [f64; 3]) -> Result<(), Exception> {
+        // Check the whole thing first so a bad pointer doesn't leave a
+        // half written vector behind.
+        self.check_writable(addr, 24)?;
+        for (i, d) in v.iter().enumerate() {
+            self.write_f64(addr.wrapping_add(8 * i as u64), *d)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_files_follow_the_map() {
+        let map = MemoryMap::default();
+        let script = map.linker_script();
+        // The linker only gets the part of ROM after the reserved bytes.
+        assert!(script.contains("rom   (rx)     : ORIGIN = 0x80000400, LENGTH = 0xffc00"));
+        assert!(script.contains("nvram (rw)     : ORIGIN = 0xa0000000, LENGTH = 0x10000"));
+        let header = map.c_header();
+        assert!(header.contains("#define RAM_BASE 0x40000000\n"));
+        assert!(header.contains("#define MMIO_SIZE 0x1000\n"));
+    }
+
+    #[test]
+    fn read_only_regions_cant_be_written_through() {
+        let rom_region = MemoryMap::default().rom;
+        let rom = Arc::new(Mutex::new(Rom::new(rom_region.size)));
+        rom.lock()
+            .unwrap()
+            .dram
+            .write(0x10, 7, cpu::DOUBLEWORD)
+            .unwrap();
+        let mut memory = GuestMemory::new();
+        memory.mount_read_only(rom_region, rom);
+
+        let addr = rom_region.base + 0x10;
+        assert_eq!(memory.read_u64(addr), Ok(7));
+        assert_eq!(
+            memory.write_u64(addr, 1),
+            Err(Exception::StoreAMOAccessFault)
+        );
+        assert_eq!(
+            memory.check_writable(addr, 8),
+            Err(Exception::StoreAMOAccessFault)
+        );
+        assert_eq!(memory.read_u64(addr), Ok(7));
+    }
+}
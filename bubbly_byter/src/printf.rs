@@ -0,0 +1,460 @@
+// Host side printf for the debuglog/snprintf system provided functions.
+// Arguments are pulled from the guest the way the RISC-V LP64D calling
+// convention passes variadic arguments: in the integer registers up to a7
+// (doubles included, as their bit pattern), then on the stack at sp.
+
+use rvemu::cpu;
+use rvemu::cpu::XRegisters;
+use rvemu::exception::Exception;
+
+use crate::memory::GuestMemory;
+
+/// Longest format string or `%s` argument that will be read from the
+/// guest. Anything past this is cut off.
+pub const MAX_STRING: u64 = 1024;
+
+pub struct VarArgs<'a> {
+    xregs: &'a XRegisters,
+    memory: &'a GuestMemory,
+    next_reg: u64,
+    next_stack: u64,
+}
+
+impl<'a> VarArgs<'a> {
+    /// Variadic arguments starting in register `first`, which is one of
+    /// `cpu::REG_A0` to `cpu::REG_A7`.
+    pub fn new(xregs: &'a XRegisters, memory: &'a GuestMemory, first: u64) -> VarArgs<'a> {
+        VarArgs {
+            xregs,
+            memory,
+            next_reg: first,
+            next_stack: xregs.read(cpu::REG_SP),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> Result<u64, Exception> {
+        if self.next_reg <= cpu::REG_A7 {
+            let v = self.xregs.read(self.next_reg);
+            self.next_reg += 1;
+            Ok(v)
+        } else {
+            let v = self.memory.read_u64(self.next_stack)?;
+            self.next_stack += 8;
+            Ok(v)
+        }
+    }
+
+    pub fn next_f64(&mut self) -> Result<f64, Exception> {
+        self.next_u64().map(f64::from_bits)
+    }
+}
+
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    /// Bytes in the argument: 1 for `hh`, 2 for `h`, 4 for none, 8 for `l`,
+    /// `ll`, `z`, `j` and `t`.
+    size: u32,
+}
+
+impl Spec {
+    /// Pads `body` out to the field width. `sign` and `prefix` go before
+    /// any zero padding.
+    fn pad(&self, sign: &str, prefix: &str, body: &[u8], zero_ok: bool) -> Vec<u8> {
+        let len = sign.len() + prefix.len() + body.len();
+        let fill = self.width.saturating_sub(len);
+        let zero_fill = !self.left && self.zero && zero_ok;
+        let mut out = vec![];
+        if !self.left && !zero_fill {
+            out.resize(fill, b' ');
+        }
+        out.extend_from_slice(sign.as_bytes());
+        out.extend_from_slice(prefix.as_bytes());
+        if zero_fill {
+            out.resize(out.len() + fill, b'0');
+        }
+        out.extend_from_slice(body);
+        if self.left {
+            out.resize(out.len() + fill, b' ');
+        }
+        out
+    }
+
+    fn sign(&self, negative: bool) -> &'static str {
+        if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    fn signed(&self, raw: u64) -> i64 {
+        match self.size {
+            1 => raw as i8 as i64,
+            2 => raw as i16 as i64,
+            4 => raw as i32 as i64,
+            _ => raw as i64,
+        }
+    }
+
+    fn unsigned(&self, raw: u64) -> u64 {
+        match self.size {
+            1 => raw as u8 as u64,
+            2 => raw as u16 as u64,
+            4 => raw as u32 as u64,
+            _ => raw,
+        }
+    }
+
+    fn integer(&self, sign: &str, prefix: &str, digits: String) -> Vec<u8> {
+        let digits = match self.precision {
+            // "%.0d" of zero prints nothing
+            Some(0) if digits == "0" => String::new(),
+            Some(p) if p > digits.len() => "0".repeat(p - digits.len()) + &digits,
+            _ => digits,
+        };
+        self.pad(sign, prefix, digits.as_bytes(), self.precision.is_none())
+    }
+
+    fn float(&self, conv: u8, v: f64) -> Vec<u8> {
+        let upper = conv.is_ascii_uppercase();
+        let sign = self.sign(v.is_sign_negative() && !v.is_nan());
+        if !v.is_finite() {
+            let s = if v.is_nan() { "nan" } else { "inf" };
+            let s = if upper {
+                s.to_uppercase()
+            } else {
+                s.to_string()
+            };
+            return self.pad(sign, "", s.as_bytes(), false);
+        }
+        let v = v.abs();
+        let precision = self.precision.unwrap_or(6);
+        let body = match conv.to_ascii_lowercase() {
+            b'f' => format!("{:.*}", precision, v),
+            b'e' => exponential(v, precision),
+            _ => {
+                let p = precision.max(1);
+                let x = exponent_of(v, p - 1);
+                let body = if x < -4 || x >= p as i32 {
+                    exponential(v, p - 1)
+                } else {
+                    format!("{:.*}", (p as i32 - 1 - x) as usize, v)
+                };
+                if self.alt {
+                    body
+                } else {
+                    strip_zeros(&body)
+                }
+            }
+        };
+        let body = if upper { body.to_uppercase() } else { body };
+        self.pad(sign, "", body.as_bytes(), true)
+    }
+}
+
+/// `v` in C's `%e` style: at least two exponent digits, always signed.
+fn exponential(v: f64, precision: usize) -> String {
+    let rust = format!("{:.*e}", precision, v);
+    let (mantissa, exp) = rust.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exp.abs())
+}
+
+/// The decimal exponent `v` has once rounded to `precision` digits after
+/// the point in `%e` style.
+fn exponent_of(v: f64, precision: usize) -> i32 {
+    let rust = format!("{:.*e}", precision, v);
+    rust.split_once('e').unwrap().1.parse().unwrap()
+}
+
+/// Drops trailing zeros after the decimal point, and the point itself if
+/// nothing is left after it, as `%g` does.
+fn strip_zeros(s: &str) -> String {
+    let (mantissa, exp) = match s.find('e') {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exp)
+}
+
+/// Formats `fmt` like C's printf family, supporting the `d i u x X o c s
+/// p f F e E g G %` conversions with the usual flags, width, precision
+/// and length modifiers. Unknown conversions are copied through as-is.
+pub fn format(memory: &GuestMemory, fmt: &[u8], args: &mut VarArgs) -> Result<Vec<u8>, Exception> {
+    let mut out = vec![];
+    let mut i = 0;
+    while i < fmt.len() {
+        if fmt[i] != b'%' {
+            out.push(fmt[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+
+        let mut spec = Spec {
+            size: 4,
+            ..Spec::default()
+        };
+        while let Some(&c) = fmt.get(i) {
+            match c {
+                b'-' => spec.left = true,
+                b'+' => spec.plus = true,
+                b' ' => spec.space = true,
+                b'#' => spec.alt = true,
+                b'0' => spec.zero = true,
+                _ => break,
+            }
+            i += 1;
+        }
+
+        if fmt.get(i) == Some(&b'*') {
+            let w = args.next_u64()? as i32;
+            spec.left |= w < 0;
+            spec.width = w.unsigned_abs() as usize;
+            i += 1;
+        } else {
+            while let Some(d) = fmt.get(i).filter(|c| c.is_ascii_digit()) {
+                spec.width = spec.width * 10 + (d - b'0') as usize;
+                i += 1;
+            }
+        }
+
+        if fmt.get(i) == Some(&b'.') {
+            i += 1;
+            if fmt.get(i) == Some(&b'*') {
+                let p = args.next_u64()? as i32;
+                spec.precision = if p < 0 { None } else { Some(p as usize) };
+                i += 1;
+            } else {
+                let mut p = 0;
+                while let Some(d) = fmt.get(i).filter(|c| c.is_ascii_digit()) {
+                    p = p * 10 + (d - b'0') as usize;
+                    i += 1;
+                }
+                spec.precision = Some(p);
+            }
+        }
+
+        while let Some(&c) = fmt.get(i) {
+            match c {
+                b'h' => spec.size = if spec.size == 2 { 1 } else { 2 },
+                b'l' | b'z' | b'j' | b't' => spec.size = 8,
+                b'L' => {}
+                _ => break,
+            }
+            i += 1;
+        }
+
+        let Some(&conv) = fmt.get(i) else {
+            out.extend_from_slice(&fmt[start..]);
+            break;
+        };
+        i += 1;
+
+        match conv {
+            b'%' => out.push(b'%'),
+            b'd' | b'i' => {
+                let v = spec.signed(args.next_u64()?);
+                out.extend(spec.integer(spec.sign(v < 0), "", v.unsigned_abs().to_string()));
+            }
+            b'u' => {
+                let v = spec.unsigned(args.next_u64()?);
+                out.extend(spec.integer("", "", v.to_string()));
+            }
+            b'o' => {
+                let v = spec.unsigned(args.next_u64()?);
+                let prefix = if spec.alt && v != 0 { "0" } else { "" };
+                out.extend(spec.integer("", prefix, format!("{:o}", v)));
+            }
+            b'x' | b'X' => {
+                let v = spec.unsigned(args.next_u64()?);
+                let (prefix, digits) = if conv == b'x' {
+                    ("0x", format!("{:x}", v))
+                } else {
+                    ("0X", format!("{:X}", v))
+                };
+                let prefix = if spec.alt && v != 0 { prefix } else { "" };
+                out.extend(spec.integer("", prefix, digits));
+            }
+            b'p' => {
+                let v = args.next_u64()?;
+                out.extend(spec.integer("", "0x", format!("{:x}", v)));
+            }
+            b'c' => {
+                let c = args.next_u64()? as u8;
+                out.extend(spec.pad("", "", &[c], false));
+            }
+            b's' => {
+                let max = spec
+                    .precision
+                    .map_or(MAX_STRING, |p| (p as u64).min(MAX_STRING));
+                let s = memory.read_cstr(args.next_u64()?, max)?;
+                out.extend(spec.pad("", "", &s, false));
+            }
+            b'f' | b'F' | b'e' | b'E' | b'g' | b'G' => {
+                out.extend(spec.float(conv, args.next_f64()?));
+            }
+            _ => out.extend_from_slice(&fmt[start..i]),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryMap;
+    use rvemu::devices::dram::Dram;
+    use std::sync::{Arc, Mutex};
+
+    /// `fmt` formatted the way `debuglogf` would: the arguments go in a1
+    /// to a7, then on the stack. Strings in `args` are written to RAM and
+    /// passed by pointer.
+    fn printf(fmt: &str, args: &[Arg]) -> String {
+        let ram = MemoryMap::default().ram;
+        let mut memory = GuestMemory::new();
+        memory.mount(ram, Arc::new(Mutex::new(Dram::new(ram.size))));
+
+        let mut xregs = XRegisters::new();
+        let sp = ram.base + 0x1000;
+        xregs.write(cpu::REG_SP, sp);
+        let mut strings = ram.base;
+        for (i, arg) in args.iter().enumerate() {
+            let raw = match arg {
+                Arg::Int(v) => *v as u64,
+                Arg::Double(v) => v.to_bits(),
+                Arg::Str(s) => {
+                    let addr = strings;
+                    memory.write_bytes(addr, s.as_bytes()).unwrap();
+                    memory.write_bytes(addr + s.len() as u64, &[0]).unwrap();
+                    strings += s.len() as u64 + 1;
+                    addr
+                }
+            };
+            match cpu::REG_A1 + i as u64 {
+                reg if reg <= cpu::REG_A7 => xregs.write(reg, raw),
+                reg => memory
+                    .write_u64(sp + 8 * (reg - cpu::REG_A7 - 1), raw)
+                    .unwrap(),
+            }
+        }
+
+        let mut va = VarArgs::new(&xregs, &memory, cpu::REG_A1);
+        String::from_utf8(format(&memory, fmt.as_bytes(), &mut va).unwrap()).unwrap()
+    }
+
+    enum Arg {
+        Int(i64),
+        Double(f64),
+        Str(&'static str),
+    }
+    use Arg::*;
+
+    #[test]
+    fn integers_and_doubles_share_the_registers() {
+        assert_eq!(
+            printf(
+                "%d %f %s %ld %g",
+                &[Int(42), Double(1.5), Str("abc"), Int(-7), Double(2.25)]
+            ),
+            "42 1.500000 abc -7 2.25"
+        );
+    }
+
+    #[test]
+    fn arguments_past_a7_come_off_the_stack() {
+        assert_eq!(
+            printf(
+                "%d %d %d %d %d %d %g %g %d",
+                &[
+                    Int(1),
+                    Int(2),
+                    Int(3),
+                    Int(4),
+                    Int(5),
+                    Int(6),
+                    Double(7.5),
+                    Double(8.25),
+                    Int(9)
+                ]
+            ),
+            "1 2 3 4 5 6 7.5 8.25 9"
+        );
+    }
+
+    #[test]
+    fn width_precision_and_flags() {
+        assert_eq!(
+            printf(
+                "[%5d|%-5d|%05d|%.3d|%+d|% d]",
+                &[Int(42), Int(42), Int(42), Int(42), Int(5), Int(5)]
+            ),
+            "[   42|42   |00042|042|+5| 5]"
+        );
+        assert_eq!(
+            printf(
+                "[%8.3f|%-9.2e|%08.2f|%.0f|%G]",
+                &[
+                    Double(1.23456),
+                    Double(1.5),
+                    Double(-2.5),
+                    Double(2.5),
+                    Double(1e-5)
+                ]
+            ),
+            "[   1.235|1.50e+00 |-0002.50|2|1E-05]"
+        );
+        assert_eq!(
+            printf(
+                "[%*d|%-*d|%.*f]",
+                &[Int(4), Int(7), Int(3), Int(7), Int(2), Double(2.5)]
+            ),
+            "[   7|7  |2.50]"
+        );
+        assert_eq!(
+            printf(
+                "[%x|%#X|%#o|%hhd|%hu|%.2s|%5s]",
+                &[
+                    Int(255),
+                    Int(255),
+                    Int(8),
+                    Int(255),
+                    Int(65537),
+                    Str("abc"),
+                    Str("ab")
+                ]
+            ),
+            "[ff|0XFF|010|-1|1|ab|   ab]"
+        );
+    }
+
+    #[test]
+    fn percent_percent_takes_no_argument() {
+        assert_eq!(printf("%d%% of %d", &[Int(50), Int(8)]), "50% of 8");
+    }
+
+    #[test]
+    fn unknown_conversions_are_copied_through() {
+        assert_eq!(printf("%q %5k %d", &[Int(3)]), "%q %5k 3");
+        assert_eq!(printf("trailing %", &[]), "trailing %");
+        assert_eq!(printf("%l", &[]), "%l");
+    }
+}
@@ -2,30 +2,56 @@
 
 use rvemu::cpu;
 use rvemu::cpu::{Cpu, FRegisters, JumpLinkHandler, XRegisters};
+use rvemu::exception::Exception;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
-pub const SPF_MIN_ADDR: u64 = 0x8000_0000;
+use crate::celestial::{KeplerianElements, KEPLARIAN_ELEMENTS_LEN};
+use crate::cost::CostTable;
+use crate::math;
+use crate::math::{add3, cross3, dot3, norm3, scale3, sub3};
+use crate::memory::{GuestMemory, CELESTIALS_OFFSET, SPF_TABLE_OFFSET};
+use crate::printf::{format, VarArgs, MAX_STRING};
+
+/// Most bytes of debug log a program can write before the host drains it.
+/// Messages past this are counted and thrown away.
+pub const MAX_LOG_BYTES: usize = 4096;
+
+/// What `horner` costs per coefficient, on top of its cost in the
+/// `CostTable`, so a long polynomial can't be evaluated for the price of
+/// a short one.
+pub const HORNER_COEFFICIENT_COST: u64 = 1;
+
+/// The system provided function table: from `SPF_TABLE_OFFSET` into ROM
+/// up to the celestial table. Calls anywhere in it are the host's.
+pub const SPF_MIN_ADDR: u64 = 0x8000_0000 + SPF_TABLE_OFFSET;
+pub const SPF_END_ADDR: u64 = 0x8000_0000 + CELESTIALS_OFFSET;
 
 pub const SPF_FCN: u64 = 0x80000400;
+pub const SPF_K_POS: u64 = 0x80000410;
 
-// pub const SPF_SNPRINTF: u64 = 0x80000420;
-// pub const SPF_DEBUGLOG: u64 = 0x80000428;
-// pub const SPF_DEBUGLOGF: u64 = 0x80000430;
-// pub const SPF_FMAX: u64 = 0x80000450;
-// pub const SPF_FMIN: u64 = 0x80000458;
+pub const SPF_SNPRINTF: u64 = 0x80000420;
+pub const SPF_DEBUGLOG: u64 = 0x80000428;
+pub const SPF_DEBUGLOGF: u64 = 0x80000430;
+pub const SPF_LFABS: u64 = 0x80000440;
+pub const SPF_FABS: u64 = 0x80000448;
+pub const SPF_FMAX: u64 = 0x80000450;
+pub const SPF_FMIN: u64 = 0x80000458;
 pub const SPF_EXP: u64 = 0x80000460;
 pub const SPF_EXPM1: u64 = 0x80000468;
 pub const SPF_LOG: u64 = 0x80000470;
 pub const SPF_LOG1P: u64 = 0x80000478;
 pub const SPF_SQRT: u64 = 0x80000480;
 pub const SPF_CBRT: u64 = 0x80000488;
-// pub const SPF_CUBE: u64 = 0x80000490;
-// pub const SPF_HYPOT2: u64 = 0x80000498;
+pub const SPF_CUBE: u64 = 0x80000490;
+pub const SPF_HYPOT2: u64 = 0x80000498;
 pub const SPF_CEIL: u64 = 0x800004a0;
 pub const SPF_FLOOR: u64 = 0x800004a8;
 pub const SPF_ROUND: u64 = 0x800004b0;
 pub const SPF_FPOWF: u64 = 0x800004b8;
-// pub const SPF_MODPOW: u64 = 0x800004c0;
-// pub const SPF_DIVMOD: u64 = 0x800004c8;
+pub const SPF_MODPOW: u64 = 0x800004c0;
+pub const SPF_DIVMOD: u64 = 0x800004c8;
 pub const SPF_SIN: u64 = 0x800004d0;
 pub const SPF_COS: u64 = 0x800004d8;
 pub const SPF_TAN: u64 = 0x800004e0;
@@ -39,45 +65,370 @@ pub const SPF_TANH: u64 = 0x80000518;
 pub const SPF_ASINH: u64 = 0x80000520;
 pub const SPF_ACOSH: u64 = 0x80000528;
 pub const SPF_ATANH: u64 = 0x80000530;
-// pub const SPF_DIST33: u64 = 0x80000538;
-// pub const SPF_ADD33: u64 = 0x80000540;
-// pub const SPF_SUB33: u64 = 0x80000548;
-// pub const SPF_MULS3: u64 = 0x80000550;
-// pub const SPF_DIVS3: u64 = 0x80000558;
-// pub const SPF_DOT33: u64 = 0x80000560;
-// pub const SPF_CROSS33: u64 = 0x80000568;
-// pub const SPF_NORM2: u64 = 0x80000570;
-// pub const SPF_NORMALIZE2: u64 = 0x80000578;
-// pub const SPF_NORM3: u64 = 0x80000580;
-// pub const SPF_NORMALIZE3: u64 = 0x80000588;
-// pub const SPF_LERP33S: u64 = 0x80000590;
-// pub const SPF_VDIST33: u64 = 0x80000598;
-// pub const SPF_VADD33: u64 = 0x800005a0;
-// pub const SPF_VSUB33: u64 = 0x800005a8;
-// pub const SPF_VMULS3: u64 = 0x800005b0;
-// pub const SPF_VDIVS3: u64 = 0x800005b8;
-// pub const SPF_VDOT33: u64 = 0x800005c0;
-// pub const SPF_VCROSS33: u64 = 0x800005c8;
-// pub const SPF_VNORM3: u64 = 0x800005d0;
-// pub const SPF_VNORMALIZE3: u64 = 0x800005d8;
-// pub const SPF_VLERP33S: u64 = 0x800005e0;
-
-pub struct SysProvided {}
+pub const SPF_DIST33: u64 = 0x80000538;
+pub const SPF_ADD33: u64 = 0x80000540;
+pub const SPF_SUB33: u64 = 0x80000548;
+pub const SPF_MULS3: u64 = 0x80000550;
+pub const SPF_DIVS3: u64 = 0x80000558;
+pub const SPF_DOT33: u64 = 0x80000560;
+pub const SPF_CROSS33: u64 = 0x80000568;
+pub const SPF_NORM2: u64 = 0x80000570;
+pub const SPF_NORMALIZE2: u64 = 0x80000578;
+pub const SPF_NORM3: u64 = 0x80000580;
+pub const SPF_NORMALIZE3: u64 = 0x80000588;
+pub const SPF_LERP33S: u64 = 0x80000590;
+pub const SPF_VDIST33: u64 = 0x80000598;
+pub const SPF_VADD33: u64 = 0x800005a0;
+pub const SPF_VSUB33: u64 = 0x800005a8;
+pub const SPF_VMULS3: u64 = 0x800005b0;
+pub const SPF_VDIVS3: u64 = 0x800005b8;
+pub const SPF_VDOT33: u64 = 0x800005c0;
+pub const SPF_VCROSS33: u64 = 0x800005c8;
+pub const SPF_VNORM3: u64 = 0x800005d0;
+pub const SPF_VNORMALIZE3: u64 = 0x800005d8;
+pub const SPF_VLERP33S: u64 = 0x800005e0;
+pub const SPF_ERF: u64 = 0x800005e8;
+pub const SPF_ERFC: u64 = 0x800005f0;
+pub const SPF_HORNER: u64 = 0x800005f8;
+pub const SPF_QUADRATICROOTS: u64 = 0x80000600;
+pub const SPF_KEPLER: u64 = 0x80000608;
+
+/// Names the functions have in `link.ld`, for cost tables and fault
+/// reports.
+pub const SPF_SYMBOLS: &[(&str, u64)] = &[
+    ("fcn", SPF_FCN),
+    ("k_pos", SPF_K_POS),
+    ("snprintf", SPF_SNPRINTF),
+    ("debuglog", SPF_DEBUGLOG),
+    ("debuglogf", SPF_DEBUGLOGF),
+    ("lfabs", SPF_LFABS),
+    ("fabs", SPF_FABS),
+    ("fmax", SPF_FMAX),
+    ("fmin", SPF_FMIN),
+    ("exp", SPF_EXP),
+    ("expm1", SPF_EXPM1),
+    ("log", SPF_LOG),
+    ("log1p", SPF_LOG1P),
+    ("sqrt", SPF_SQRT),
+    ("cbrt", SPF_CBRT),
+    ("cube", SPF_CUBE),
+    ("hypot2", SPF_HYPOT2),
+    ("ceil", SPF_CEIL),
+    ("floor", SPF_FLOOR),
+    ("round", SPF_ROUND),
+    ("pow", SPF_FPOWF),
+    ("modpow", SPF_MODPOW),
+    ("divmod", SPF_DIVMOD),
+    ("sin", SPF_SIN),
+    ("cos", SPF_COS),
+    ("tan", SPF_TAN),
+    ("asin", SPF_ASIN),
+    ("acos", SPF_ACOS),
+    ("atan", SPF_ATAN),
+    ("atan2", SPF_ATAN2),
+    ("sinh", SPF_SINH),
+    ("cosh", SPF_COSH),
+    ("tanh", SPF_TANH),
+    ("asinh", SPF_ASINH),
+    ("acosh", SPF_ACOSH),
+    ("atanh", SPF_ATANH),
+    ("dist33", SPF_DIST33),
+    ("add33", SPF_ADD33),
+    ("sub33", SPF_SUB33),
+    ("muls3", SPF_MULS3),
+    ("divs3", SPF_DIVS3),
+    ("dot33", SPF_DOT33),
+    ("cross33", SPF_CROSS33),
+    ("norm2", SPF_NORM2),
+    ("normalize2", SPF_NORMALIZE2),
+    ("norm3", SPF_NORM3),
+    ("normalize3", SPF_NORMALIZE3),
+    ("lerp33s", SPF_LERP33S),
+    ("vdist33", SPF_VDIST33),
+    ("vadd33", SPF_VADD33),
+    ("vsub33", SPF_VSUB33),
+    ("vmuls3", SPF_VMULS3),
+    ("vdivs3", SPF_VDIVS3),
+    ("vdot33", SPF_VDOT33),
+    ("vcross33", SPF_VCROSS33),
+    ("vnorm3", SPF_VNORM3),
+    ("vnormalize3", SPF_VNORMALIZE3),
+    ("vlerp33s", SPF_VLERP33S),
+    ("erf", SPF_ERF),
+    ("erfc", SPF_ERFC),
+    ("horner", SPF_HORNER),
+    ("quadraticroots", SPF_QUADRATICROOTS),
+    ("kepler", SPF_KEPLER),
+];
+
+pub fn spf_address(name: &str) -> Option<u64> {
+    SPF_SYMBOLS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, addr)| *addr)
+}
+
+pub fn spf_name(addr: u64) -> Option<&'static str> {
+    SPF_SYMBOLS
+        .iter()
+        .find(|(_, a)| *a == addr)
+        .map(|(name, _)| *name)
+}
+
+/// Something a system provided function couldn't do. The program is
+/// stopped rather than carrying on with a half-done call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpfFault {
+    /// A pointer argument didn't point at mapped memory.
+    Memory { spf: u64, exception: Exception },
+    /// A jump to an address in the system provided range that has no
+    /// implementation. `symbol` is its name in the firmware's link map.
+    Unimplemented { addr: u64, symbol: Option<String> },
+}
+
+impl fmt::Display for SpfFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpfFault::Memory { spf, exception } => {
+                let name = spf_name(*spf).unwrap_or("?");
+                write!(
+                    f,
+                    "{} ({:#x}) was passed a bad pointer: {:?}",
+                    name, spf, exception
+                )
+            }
+            SpfFault::Unimplemented {
+                addr,
+                symbol: Some(symbol),
+            } => write!(f, "{} ({:#x}) isn't implemented", symbol, addr),
+            SpfFault::Unimplemented { addr, symbol: None } => {
+                write!(f, "nothing is provided at {:#x}", addr)
+            }
+        }
+    }
+}
+
+/// What the handler has to tell the emulator after a call. Shared between
+/// `SysProvided`, which the cpu owns, and `BubblyByter`.
+#[derive(Debug, Default)]
+pub struct SpfState {
+    pub fault: Option<SpfFault>,
+    pub costs: CostTable,
+    /// What the call the cpu just made costs, for the budget to charge in
+    /// place of the single `jal`.
+    pub charge: Option<u64>,
+    /// The loaded firmware's symbols in the system provided range, by
+    /// address, to name calls that have no implementation.
+    pub link_map: HashMap<u64, String>,
+    /// Messages from `debuglog` and `debuglogf`, oldest first.
+    pub log: Vec<Vec<u8>>,
+    pub log_bytes: usize,
+    /// Messages that didn't fit in the log.
+    pub log_dropped: usize,
+}
+
+impl SpfState {
+    fn push_log(&mut self, message: Vec<u8>) {
+        if self.log_bytes + message.len() > MAX_LOG_BYTES {
+            self.log_dropped += 1;
+        } else {
+            self.log_bytes += message.len();
+            self.log.push(message);
+        }
+    }
+}
+
+pub struct SysProvided {
+    memory: GuestMemory,
+    state: Arc<Mutex<SpfState>>,
+}
+
+impl SysProvided {
+    pub fn new(memory: GuestMemory, state: Arc<Mutex<SpfState>>) -> SysProvided {
+        SysProvided { memory, state }
+    }
+
+    /// Stops the program if a function that takes pointers was given a
+    /// bad one.
+    fn memory_fault(&self, spf: u64, result: Result<(), Exception>) {
+        if let Err(exception) = result {
+            self.state.lock().unwrap().fault = Some(SpfFault::Memory { spf, exception });
+        }
+    }
+
+    /// `snprintf`, `debuglog` and `debuglogf`.
+    fn logging(&self, spf: u64, xregs: &mut XRegisters) -> Result<(), Exception> {
+        let m = &self.memory;
+        let a0 = xregs.read(cpu::REG_A0);
+        let a1 = xregs.read(cpu::REG_A1);
+        match spf {
+            SPF_SNPRINTF => {
+                let fmt = m.read_cstr(xregs.read(cpu::REG_A2), MAX_STRING)?;
+                let out = format(m, &fmt, &mut VarArgs::new(xregs, m, cpu::REG_A3))?;
+                if a1 > 0 {
+                    let n = out.len().min(a1 as usize - 1);
+                    let mut written = out[..n].to_vec();
+                    written.push(0);
+                    m.write_bytes(a0, &written)?;
+                }
+                // int, so sign extended
+                xregs.write(cpu::REG_A0, out.len() as i32 as i64 as u64);
+            }
+            SPF_DEBUGLOG => {
+                let message = m.read_cstr(a0, a1.min(MAX_STRING))?;
+                self.state.lock().unwrap().push_log(message);
+            }
+            SPF_DEBUGLOGF => {
+                let fmt = m.read_cstr(a0, MAX_STRING)?;
+                let message = format(m, &fmt, &mut VarArgs::new(xregs, m, cpu::REG_A1))?;
+                self.state.lock().unwrap().push_log(message);
+            }
+            _ => unreachable!("{:#x} isn't a logging function", spf),
+        }
+        Ok(())
+    }
+
+    /// `horner` and `quadraticroots`, which take their coefficients or
+    /// return their roots through memory.
+    fn polynomial(
+        &self,
+        spf: u64,
+        xregs: &mut XRegisters,
+        fregs: &mut FRegisters,
+    ) -> Result<(), Exception> {
+        let m = &self.memory;
+        let a0 = xregs.read(cpu::REG_A0);
+        match spf {
+            SPF_HORNER => {
+                let n = xregs.read(cpu::REG_A1);
+                if let Some(charge) = &mut self.state.lock().unwrap().charge {
+                    *charge = charge.saturating_add(n.saturating_mul(HORNER_COEFFICIENT_COST));
+                }
+                let c = (0..n)
+                    .map(|i| m.read_f64(a0.wrapping_add(8 * i)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                fregs.write(cpu::REG_FA0, math::horner(&c, fregs.read(cpu::REG_FA0)));
+            }
+            SPF_QUADRATICROOTS => {
+                let roots = math::quadratic_roots(
+                    fregs.read(cpu::REG_FA0),
+                    fregs.read(cpu::REG_FA1),
+                    fregs.read(cpu::REG_FA2),
+                );
+                // Checked first so a bad pointer doesn't leave one root
+                // written.
+                m.check_writable(a0, 8 * roots.len() as u64)?;
+                for (i, r) in roots.iter().enumerate() {
+                    m.write_f64(a0.wrapping_add(8 * i as u64), *r)?;
+                }
+                xregs.write(cpu::REG_A0, roots.len() as u64);
+            }
+            _ => unreachable!("{:#x} isn't a polynomial function", spf),
+        }
+        Ok(())
+    }
+
+    /// `k_pos(vec3_t *res, struct keplarian_elements *k, double t)`.
+    fn k_pos(&self, xregs: &XRegisters, fregs: &FRegisters) -> Result<(), Exception> {
+        let m = &self.memory;
+        let k = xregs.read(cpu::REG_A1);
+        let mut d = [0.0; KEPLARIAN_ELEMENTS_LEN];
+        for (i, v) in d.iter_mut().enumerate() {
+            *v = m.read_f64(k.wrapping_add(8 * i as u64))?;
+        }
+        let position = KeplerianElements::from_doubles(d).position_at(fregs.read(cpu::REG_FA0));
+        m.write_vec3(xregs.read(cpu::REG_A0), position)
+    }
+
+    /// The functions that take or return `double *`/`vec3_t *`. A
+    /// `vec3_t` is three packed doubles, so each `v` function is the same
+    /// as its plain twin.
+    fn vector(
+        &self,
+        spf: u64,
+        xregs: &XRegisters,
+        fregs: &mut FRegisters,
+    ) -> Result<(), Exception> {
+        let m = &self.memory;
+        let a0 = xregs.read(cpu::REG_A0);
+        let a1 = xregs.read(cpu::REG_A1);
+        let a2 = xregs.read(cpu::REG_A2);
+        let fa0 = fregs.read(cpu::REG_FA0);
+        match spf {
+            SPF_DIST33 | SPF_VDIST33 => fregs.write(
+                cpu::REG_FA0,
+                norm3(sub3(m.read_vec3(a0)?, m.read_vec3(a1)?)),
+            ),
+            SPF_ADD33 | SPF_VADD33 => m.write_vec3(a0, add3(m.read_vec3(a1)?, m.read_vec3(a2)?))?,
+            SPF_SUB33 | SPF_VSUB33 => m.write_vec3(a0, sub3(m.read_vec3(a1)?, m.read_vec3(a2)?))?,
+            SPF_MULS3 | SPF_VMULS3 => m.write_vec3(a0, scale3(m.read_vec3(a1)?, fa0))?,
+            SPF_DIVS3 | SPF_VDIVS3 => m.write_vec3(a0, scale3(m.read_vec3(a1)?, 1.0 / fa0))?,
+            SPF_DOT33 | SPF_VDOT33 => m.write_f64(a0, dot3(m.read_vec3(a1)?, m.read_vec3(a2)?))?,
+            SPF_CROSS33 | SPF_VCROSS33 => {
+                m.write_vec3(a0, cross3(m.read_vec3(a1)?, m.read_vec3(a2)?))?
+            }
+            SPF_NORM2 => {
+                let [x, y] = m.read_vec2(a0)?;
+                fregs.write(cpu::REG_FA0, x.hypot(y))
+            }
+            SPF_NORMALIZE2 => {
+                let [x, y] = m.read_vec2(a0)?;
+                let n = x.hypot(y);
+                if n != 0.0 {
+                    m.write_vec2(a0, [x / n, y / n])?;
+                }
+            }
+            SPF_NORM3 | SPF_VNORM3 => fregs.write(cpu::REG_FA0, norm3(m.read_vec3(a0)?)),
+            SPF_NORMALIZE3 | SPF_VNORMALIZE3 => {
+                let v = m.read_vec3(a0)?;
+                let n = norm3(v);
+                if n != 0.0 {
+                    m.write_vec3(a0, scale3(v, 1.0 / n))?;
+                }
+            }
+            SPF_LERP33S | SPF_VLERP33S => {
+                let a = m.read_vec3(a1)?;
+                let b = m.read_vec3(a2)?;
+                m.write_vec3(a0, add3(a, scale3(sub3(b, a), fa0)))?
+            }
+            _ => unreachable!("{:#x} isn't a vector function", spf),
+        }
+        Ok(())
+    }
+}
 
 impl JumpLinkHandler for SysProvided {
     fn should_handle(&self, new_pc: u64) -> bool {
-        new_pc >= SPF_MIN_ADDR
+        (SPF_MIN_ADDR..SPF_END_ADDR).contains(&new_pc)
     }
     fn handle(&self, new_pc: u64, cpu: &Cpu) -> (XRegisters, FRegisters) {
-        let xregs = cpu.xregs.clone();
+        let mut xregs = cpu.xregs.clone();
         let mut fregs = cpu.fregs.clone();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.charge = Some(state.costs.cost(new_pc));
+        }
         match new_pc {
+            SPF_FABS => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).abs()),
+            SPF_FMAX => fregs.write(
+                cpu::REG_FA0,
+                fregs.read(cpu::REG_FA0).max(fregs.read(cpu::REG_FA1)),
+            ),
+            SPF_FMIN => fregs.write(
+                cpu::REG_FA0,
+                fregs.read(cpu::REG_FA0).min(fregs.read(cpu::REG_FA1)),
+            ),
             SPF_EXP => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).exp()),
             SPF_EXPM1 => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).exp_m1()),
             SPF_LOG => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).ln()),
             SPF_LOG1P => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).ln_1p()),
             SPF_SQRT => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).sqrt()),
             SPF_CBRT => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).cbrt()),
+            SPF_CUBE => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).powi(3)),
+            SPF_HYPOT2 => fregs.write(
+                cpu::REG_FA0,
+                fregs.read(cpu::REG_FA0).hypot(fregs.read(cpu::REG_FA1)),
+            ),
             SPF_CEIL => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).ceil()),
             SPF_FLOOR => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).floor()),
             SPF_ROUND => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).round()),
@@ -85,6 +436,33 @@ impl JumpLinkHandler for SysProvided {
                 cpu::REG_FA0,
                 fregs.read(cpu::REG_FA0).powf(fregs.read(cpu::REG_FA1)),
             ),
+            SPF_LFABS => xregs.write(
+                cpu::REG_A0,
+                (xregs.read(cpu::REG_A0) as i64).wrapping_abs() as u64,
+            ),
+            SPF_MODPOW => xregs.write(
+                cpu::REG_A0,
+                math::modpow(
+                    xregs.read(cpu::REG_A0) as i64,
+                    xregs.read(cpu::REG_A1) as i64,
+                    xregs.read(cpu::REG_A2) as i64,
+                ) as u64,
+            ),
+            SPF_DIVMOD => {
+                let (q, r) = math::divmod(
+                    xregs.read(cpu::REG_A2) as i64,
+                    xregs.read(cpu::REG_A3) as i64,
+                );
+                let (q_ptr, r_ptr) = (xregs.read(cpu::REG_A0), xregs.read(cpu::REG_A1));
+                // Both checked first so a bad `r` doesn't leave `q` written.
+                let written = self
+                    .memory
+                    .check_writable(q_ptr, 8)
+                    .and_then(|_| self.memory.check_writable(r_ptr, 8))
+                    .and_then(|_| self.memory.write_u64(q_ptr, q as u64))
+                    .and_then(|_| self.memory.write_u64(r_ptr, r as u64));
+                self.memory_fault(new_pc, written);
+            }
             SPF_SIN => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).sin()),
             SPF_COS => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).cos()),
             SPF_TAN => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).tan()),
@@ -101,12 +479,349 @@ impl JumpLinkHandler for SysProvided {
             SPF_ASINH => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).asinh()),
             SPF_ACOSH => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).acosh()),
             SPF_ATANH => fregs.write(cpu::REG_FA0, fregs.read(cpu::REG_FA0).atanh()),
+            SPF_SNPRINTF | SPF_DEBUGLOG | SPF_DEBUGLOGF => {
+                self.memory_fault(new_pc, self.logging(new_pc, &mut xregs));
+            }
+            SPF_DIST33 | SPF_ADD33 | SPF_SUB33 | SPF_MULS3 | SPF_DIVS3 | SPF_DOT33
+            | SPF_CROSS33 | SPF_NORM2 | SPF_NORMALIZE2 | SPF_NORM3 | SPF_NORMALIZE3
+            | SPF_LERP33S | SPF_VDIST33 | SPF_VADD33 | SPF_VSUB33 | SPF_VMULS3 | SPF_VDIVS3
+            | SPF_VDOT33 | SPF_VCROSS33 | SPF_VNORM3 | SPF_VNORMALIZE3 | SPF_VLERP33S => {
+                self.memory_fault(new_pc, self.vector(new_pc, &xregs, &mut fregs));
+            }
+            SPF_ERF => fregs.write(cpu::REG_FA0, math::erf(fregs.read(cpu::REG_FA0))),
+            SPF_ERFC => fregs.write(cpu::REG_FA0, math::erfc(fregs.read(cpu::REG_FA0))),
+            SPF_KEPLER => fregs.write(
+                cpu::REG_FA0,
+                math::kepler(fregs.read(cpu::REG_FA0), fregs.read(cpu::REG_FA1)),
+            ),
+            SPF_HORNER | SPF_QUADRATICROOTS => {
+                self.memory_fault(new_pc, self.polynomial(new_pc, &mut xregs, &mut fregs));
+            }
             SPF_FCN => {
                 fregs.write(cpu::REG_FA1, 654.321);
             }
-            _ => {}
+            SPF_K_POS => {
+                self.memory_fault(new_pc, self.k_pos(&xregs, &fregs));
+            }
+            _ => {
+                let mut state = self.state.lock().unwrap();
+                let symbol = state.link_map.get(&new_pc).cloned();
+                state.fault = Some(SpfFault::Unimplemented {
+                    addr: new_pc,
+                    symbol,
+                });
+            }
         }
 
         (xregs, fregs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryMap;
+    use rvemu::devices::dram::Dram;
+
+    const RAM: u64 = 0x4000_0000;
+
+    /// A `SysProvided` with nothing but RAM mounted, and the memory and
+    /// state it shares.
+    struct Harness {
+        sys: SysProvided,
+        memory: GuestMemory,
+        state: Arc<Mutex<SpfState>>,
+    }
+
+    impl Harness {
+        fn new() -> Harness {
+            let ram = MemoryMap::default().ram;
+            assert_eq!(ram.base, RAM);
+            let mut memory = GuestMemory::new();
+            memory.mount(ram, Arc::new(Mutex::new(Dram::new(ram.size))));
+            let state = Arc::new(Mutex::new(SpfState::default()));
+            Harness {
+                sys: SysProvided::new(memory.clone(), state.clone()),
+                memory,
+                state,
+            }
+        }
+
+        /// Calls `spf` with the given integer and float argument registers.
+        fn call(&self, spf: u64, x: &[(u64, u64)], f: &[(u64, f64)]) -> (XRegisters, FRegisters) {
+            let mut c = Cpu::new();
+            for &(reg, v) in x {
+                c.xregs.write(reg, v);
+            }
+            for &(reg, v) in f {
+                c.fregs.write(reg, v);
+            }
+            self.sys.handle(spf, &c)
+        }
+
+        fn fault(&self) -> Option<SpfFault> {
+            self.state.lock().unwrap().fault.clone()
+        }
+    }
+
+    /// `snprintf(buf, size, "%s=%d", "hello", 12345)`: what it returned and
+    /// the first 16 bytes of `buf`, which start out as 0xff.
+    fn snprintf(size: u64) -> (i64, Vec<u8>) {
+        let h = Harness::new();
+        let (buf, fmt, s) = (RAM, RAM + 0x100, RAM + 0x200);
+        h.memory.write_bytes(buf, &[0xff; 16]).unwrap();
+        h.memory.write_bytes(fmt, b"%s=%d\0").unwrap();
+        h.memory.write_bytes(s, b"hello\0").unwrap();
+        let (xregs, _) = h.call(
+            SPF_SNPRINTF,
+            &[
+                (cpu::REG_A0, buf),
+                (cpu::REG_A1, size),
+                (cpu::REG_A2, fmt),
+                (cpu::REG_A3, s),
+                (cpu::REG_A4, 12345),
+            ],
+            &[],
+        );
+        assert_eq!(h.fault(), None);
+        (
+            xregs.read(cpu::REG_A0) as i64,
+            h.memory.read_bytes(buf, 16).unwrap(),
+        )
+    }
+
+    #[test]
+    fn divmod_writes_both_results() {
+        let h = Harness::new();
+        h.call(
+            SPF_DIVMOD,
+            &[
+                (cpu::REG_A0, RAM),
+                (cpu::REG_A1, RAM + 8),
+                (cpu::REG_A2, -7i64 as u64),
+                (cpu::REG_A3, 2),
+            ],
+            &[],
+        );
+        assert_eq!(h.fault(), None);
+        let (q, r) = math::divmod(-7, 2);
+        assert_eq!(h.memory.read_u64(RAM).unwrap(), q as u64);
+        assert_eq!(h.memory.read_u64(RAM + 8).unwrap(), r as u64);
+    }
+
+    #[test]
+    fn divmod_writes_nothing_if_either_pointer_is_bad() {
+        let h = Harness::new();
+        h.memory.write_u64(RAM, 0xdead).unwrap();
+        h.call(
+            SPF_DIVMOD,
+            &[
+                (cpu::REG_A0, RAM),
+                (cpu::REG_A1, 0x10),
+                (cpu::REG_A2, 7),
+                (cpu::REG_A3, 2),
+            ],
+            &[],
+        );
+        assert!(matches!(
+            h.fault(),
+            Some(SpfFault::Memory {
+                spf: SPF_DIVMOD,
+                ..
+            })
+        ));
+        assert_eq!(h.memory.read_u64(RAM).unwrap(), 0xdead);
+    }
+
+    #[test]
+    fn horner_is_charged_per_coefficient() {
+        let h = Harness::new();
+        h.state.lock().unwrap().costs.set(SPF_HORNER, 3);
+        for (i, c) in [1.0, 2.0, 3.0, 4.0, 5.0].iter().enumerate() {
+            h.memory.write_f64(RAM + 8 * i as u64, *c).unwrap();
+        }
+        let (_, fregs) = h.call(
+            SPF_HORNER,
+            &[(cpu::REG_A0, RAM), (cpu::REG_A1, 5)],
+            &[(cpu::REG_FA0, 2.0)],
+        );
+        assert_eq!(h.fault(), None);
+        assert_eq!(fregs.read(cpu::REG_FA0), 129.0);
+        assert_eq!(
+            h.state.lock().unwrap().charge,
+            Some(3 + 5 * HORNER_COEFFICIENT_COST)
+        );
+    }
+
+    #[test]
+    fn snprintf_fits() {
+        let (len, buf) = snprintf(16);
+        assert_eq!(len, 11);
+        assert_eq!(&buf[..12], b"hello=12345\0");
+        assert_eq!(buf[12..], [0xff; 4]);
+    }
+
+    #[test]
+    fn snprintf_truncates_at_the_buffer_size() {
+        // The full length is returned, but only size - 1 bytes and the NUL
+        // are written.
+        let (len, buf) = snprintf(8);
+        assert_eq!(len, 11);
+        assert_eq!(&buf[..8], b"hello=1\0");
+        assert_eq!(buf[8..], [0xff; 8]);
+
+        let (len, buf) = snprintf(1);
+        assert_eq!(len, 11);
+        assert_eq!(buf[0], 0);
+        assert_eq!(buf[1..], [0xff; 15]);
+    }
+
+    #[test]
+    fn snprintf_of_size_zero_writes_nothing() {
+        let (len, buf) = snprintf(0);
+        assert_eq!(len, 11);
+        assert_eq!(buf, [0xff; 16]);
+    }
+
+    #[test]
+    fn calls_are_only_taken_in_the_function_table() {
+        let map = MemoryMap::default();
+        assert_eq!(SPF_MIN_ADDR, map.rom.base + SPF_TABLE_OFFSET);
+        let h = Harness::new();
+        assert!(h.sys.should_handle(SPF_FCN));
+        assert!(h.sys.should_handle(SPF_VLERP33S));
+        assert!(h.sys.should_handle(SPF_END_ADDR - 8));
+        assert!(!h.sys.should_handle(map.ram.base));
+        assert!(!h.sys.should_handle(map.rom.base));
+        assert!(!h.sys.should_handle(map.rom.base + CELESTIALS_OFFSET));
+        assert!(!h.sys.should_handle(map.mmio.base));
+        assert!(!h.sys.should_handle(map.nvram.base));
+    }
+
+    #[test]
+    fn dot_and_cross() {
+        let h = Harness::new();
+        let (a, b, out) = (RAM, RAM + 0x20, RAM + 0x40);
+        h.memory.write_vec3(a, [1.0, 2.0, 3.0]).unwrap();
+        h.memory.write_vec3(b, [4.0, -5.0, 6.0]).unwrap();
+
+        h.call(
+            SPF_DOT33,
+            &[(cpu::REG_A0, out), (cpu::REG_A1, a), (cpu::REG_A2, b)],
+            &[],
+        );
+        assert_eq!(h.memory.read_f64(out).unwrap(), 12.0);
+
+        h.call(
+            SPF_VCROSS33,
+            &[(cpu::REG_A0, out), (cpu::REG_A1, a), (cpu::REG_A2, b)],
+            &[],
+        );
+        assert_eq!(h.memory.read_vec3(out).unwrap(), [27.0, 6.0, -13.0]);
+        assert_eq!(h.fault(), None);
+    }
+
+    #[test]
+    fn normalize_scales_to_unit_length() {
+        let h = Harness::new();
+        h.memory.write_vec3(RAM, [0.0, -4.0, 0.0]).unwrap();
+        h.call(SPF_NORMALIZE3, &[(cpu::REG_A0, RAM)], &[]);
+        assert_eq!(h.memory.read_vec3(RAM).unwrap(), [0.0, -1.0, 0.0]);
+
+        h.memory.write_vec2(RAM, [-3.0, 4.0]).unwrap();
+        let (_, fregs) = h.call(SPF_NORM2, &[(cpu::REG_A0, RAM)], &[]);
+        assert_eq!(fregs.read(cpu::REG_FA0), 5.0);
+        h.call(SPF_NORMALIZE2, &[(cpu::REG_A0, RAM)], &[]);
+        assert_eq!(h.memory.read_vec2(RAM).unwrap(), [-0.6, 0.8]);
+        assert_eq!(h.fault(), None);
+    }
+
+    #[test]
+    fn normalize_leaves_the_zero_vector_alone() {
+        let h = Harness::new();
+        h.call(SPF_VNORMALIZE3, &[(cpu::REG_A0, RAM)], &[]);
+        assert_eq!(h.memory.read_vec3(RAM).unwrap(), [0.0; 3]);
+        h.call(SPF_NORMALIZE2, &[(cpu::REG_A0, RAM)], &[]);
+        assert_eq!(h.memory.read_vec2(RAM).unwrap(), [0.0; 2]);
+        assert_eq!(h.fault(), None);
+    }
+
+    #[test]
+    fn vector_functions_fault_on_bad_pointers() {
+        let h = Harness::new();
+        h.call(
+            SPF_CROSS33,
+            &[
+                (cpu::REG_A0, RAM),
+                (cpu::REG_A1, u64::MAX - 8),
+                (cpu::REG_A2, RAM),
+            ],
+            &[],
+        );
+        assert!(matches!(
+            h.fault(),
+            Some(SpfFault::Memory {
+                spf: SPF_CROSS33,
+                exception: Exception::LoadAccessFault,
+            })
+        ));
+
+        // Wrapping past the top of the address space.
+        let h = Harness::new();
+        h.call(SPF_NORM2, &[(cpu::REG_A0, u64::MAX - 7)], &[]);
+        assert!(matches!(
+            h.fault(),
+            Some(SpfFault::Memory { spf: SPF_NORM2, .. })
+        ));
+        // Straddling the end of RAM: the half that's there is left alone.
+        let h = Harness::new();
+        let end = RAM + MemoryMap::default().ram.size;
+        h.memory.write_f64(end - 8, 2.0).unwrap();
+        h.call(SPF_NORMALIZE2, &[(cpu::REG_A0, end - 8)], &[]);
+        assert!(h.fault().is_some());
+        assert_eq!(h.memory.read_f64(end - 8).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn quadratic_roots_are_written_smallest_first() {
+        let h = Harness::new();
+        let (xregs, _) = h.call(
+            SPF_QUADRATICROOTS,
+            &[(cpu::REG_A0, RAM)],
+            &[
+                (cpu::REG_FA0, 1.0),
+                (cpu::REG_FA1, -3.0),
+                (cpu::REG_FA2, 2.0),
+            ],
+        );
+        assert_eq!(h.fault(), None);
+        assert_eq!(xregs.read(cpu::REG_A0), 2);
+        assert_eq!(h.memory.read_vec2(RAM).unwrap(), [1.0, 2.0]);
+    }
+
+    #[test]
+    fn quadratic_roots_write_nothing_through_a_bad_pointer() {
+        let end = RAM + MemoryMap::default().ram.size;
+        for out in [end - 8, u64::MAX - 7] {
+            let h = Harness::new();
+            h.memory.write_f64(end - 8, 5.0).unwrap();
+            h.call(
+                SPF_QUADRATICROOTS,
+                &[(cpu::REG_A0, out)],
+                &[
+                    (cpu::REG_FA0, 1.0),
+                    (cpu::REG_FA1, -3.0),
+                    (cpu::REG_FA2, 2.0),
+                ],
+            );
+            assert!(matches!(
+                h.fault(),
+                Some(SpfFault::Memory {
+                    spf: SPF_QUADRATICROOTS,
+                    exception: Exception::StoreAMOAccessFault,
+                })
+            ));
+            assert_eq!(h.memory.read_f64(end - 8).unwrap(), 5.0);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+# Fixture for the ELF loader tests in base_system.rs. main calls `sqrt`,
+# keeps the result in `saved`, then calls `memset`, which has a slot in
+# link.ld but no implementation. All three are placed by link.ld alone.
+#
+# link_map.elf is this and bubbly_byter_cc's start.s, assembled with
+#   llvm-mc -triple=riscv64 -mattr=+m,+a,+f,+d,+c,-relax -filetype=obj
+# and linked against gen_memmap's output with
+#   ld.lld -z max-page-size=16 -L build -T link.ld
+# after dropping the `a` and `i` attributes lld doesn't take from ram in
+# memmap.ld.
+
+    .section .text
+    .globl main
+main:
+    addi sp, sp, -16
+    sd ra, 8(sp)
+    li t0, 0x4030000000000000   # 16.0
+    fmv.d.x fa0, t0
+    call sqrt
+    lla t0, saved
+    fsd fa0, 0(t0)
+    call memset                 # not implemented
+    ld ra, 8(sp)
+    addi sp, sp, 16
+    ret
@@ -4,8 +4,36 @@
 
 #include <stdint.h>
 
+#include "memmap.h"
+
 typedef uint64_t size_t;
 
+// ecall numbers, selected by a7. Returning from main is the same as
+// calling exit with main's return value.
+#define SYS_EXIT  0
+#define SYS_YIELD 1
+#define SYS_ABORT 2
+
+static inline void __attribute__((noreturn)) exit(int64_t code) {
+  register int64_t a0 asm("a0") = code;
+  register int64_t a7 asm("a7") = SYS_EXIT;
+  asm volatile("ecall" : : "r"(a0), "r"(a7) : "memory");
+  __builtin_unreachable();
+}
+
+// Ends this tick without using the rest of the instruction budget.
+static inline void yield(void) {
+  register int64_t a7 asm("a7") = SYS_YIELD;
+  asm volatile("ecall" : : "r"(a7) : "memory");
+}
+
+static inline void __attribute__((noreturn)) abort(int64_t reason) {
+  register int64_t a0 asm("a0") = reason;
+  register int64_t a7 asm("a7") = SYS_ABORT;
+  asm volatile("ecall" : : "r"(a0), "r"(a7) : "memory");
+  __builtin_unreachable();
+}
+
 struct __attribute__((packed, aligned(8))) vec3 {
   double x;
   double y;
@@ -14,50 +42,127 @@ struct __attribute__((packed, aligned(8))) vec3 {
 
 typedef struct vec3 vec3_t;
 
+// Lengths in meters, angles in radians, epoch is a Julian date.
 struct __attribute__((packed, aligned(8))) keplarian_elements {
-  double e;
-  double a;
-  double b;
-  double p;
-  double ra;
-  double rp;
+  double e;     // eccentricity
+  double a;     // semi-major axis
+  double b;     // semi-minor axis
+  double p;     // semi-latus rectum
+  double ra;    // apoapsis radius
+  double rp;    // periapsis radius
+  double i;     // inclination
+  double lan;   // longitude of the ascending node
+  double argp;  // argument of periapsis
+  double m0;    // mean anomaly at epoch
+  double epoch;
+  double mu;    // G * M of the parent, m^3 s^-2
 };
 
 extern struct keplarian_elements keplarian_elements[10];
 
+// Devices are memory mapped; see bubbly_byter's devices module. Every
+// register is a double and has to be read or written whole: other accesses,
+// and writes to anything marked read only, fault.
+
+// The simulation's Julian date. Read only.
 extern struct __attribute__((packed, aligned(8))) {
   double current;
 } chrono;
 
+struct __attribute__((packed, aligned(8))) thruster {
+  double throttle;            // 0 to 1, set by the program
+  double max_mass_flow_rate;  // kg/s, read only
+  double specific_impulse;    // s, read only
+};
+
+#define THRUSTER_PITCH 0
+#define THRUSTER_ROLL  1
+#define THRUSTER_YAW   2
+#define THRUSTER_X     0
+#define THRUSTER_Y     1
+#define THRUSTER_Z     2
+
+// Out of range throttles are clamped as they're written.
+extern struct __attribute__((packed, aligned(8))) {
+  struct thruster attitude[3];
+  struct thruster position[3];
+} thrusters;
+
+// Where the ship thinks it is: meters and meters per second from the solar
+// system barycenter, and pitch, roll and yaw in radians. Read only, and
+// only as good as the unit fitted; readings are noisy and drift.
+extern struct __attribute__((packed, aligned(8))) {
+  vec3_t position;
+  vec3_t velocity;
+  vec3_t orientation;
+} irs;
+
+struct __attribute__((packed, aligned(8))) adf_channel {
+  double frequency;  // set by the program, 0 is off
+  double locked;     // 1 if a beacon is on frequency, else 0 and the rest NaN
+  double distance;   // m
+  double pitch;      // radians above the nose
+  double yaw;        // radians left of the nose
+};
+
+// Direction finder for nav beacons. Bearings are taken before each tick,
+// so a channel tuned during a tick reads its beacon from the next one.
+// N_ADF_CHANNELS comes from memmap.h.
+extern struct __attribute__((packed, aligned(8))) {
+  struct adf_channel channel[N_ADF_CHANNELS];
+} adf;
+
+// Masses in kg. Read only. The thrusters burn propellant as they fire and
+// are cut when it runs out.
+extern struct __attribute__((packed, aligned(8))) {
+  double propellant;
+  double capacity;
+  double mass;            // the whole craft, propellant and cargo included
+  double cargo_mass;
+  double max_cargo_mass;
+} fuel;
+
 
 extern char __attribute__((aligned(8))) *saved;
 
 extern double fcn(double d);
 extern void x(double *v);
 extern void xx();
+// Position relative to the parent at Julian date t. NaN unless the orbit
+// is elliptical.
 extern void k_pos(vec3_t *res, struct keplarian_elements *k, double t);
+// Formatting supports %d %i %u %x %X %o %c %s %p %f %e %g (and upper
+// case) with flags, width, precision and the h/l/z length modifiers.
+// Each debuglog/debuglogf call is one message in the ship's log, which the
+// host drains after every tick. The log holds 4K per tick; messages past
+// that are dropped.
 extern int snprintf(char *buffer, size_t bufsz, const char *format, ...);
 extern void debuglog(char *, size_t bufsz);
 extern void debuglogf(const char *format, ...);
 extern void *memset(void *dest, int ch, size_t count);
 
 
-extern int64_t lfabs(int64_t);
+extern int64_t lfabs(int64_t);  // lfabs(INT64_MIN) == INT64_MIN
 extern double  fabs(double);
 extern double  fmax(double, double);
 extern double  fmin(double, double);
 extern double  exp(double);
 extern double  expm1(double);
-extern double  ln(double);
-extern double  ln1p(double);
+extern double  log(double);
+extern double  log1p(double);
 extern double  sqrt(double);
 extern double  cbrt(double);
+extern double  cube(double);
 extern double  hypot2(double, double);
 extern double  ceil(double);
 extern double  floor(double);
 extern double  round(double);
-extern double  fpowf(double, double);
+extern double  pow(double, double);
+// (i ** p) % m in [0, |m|). Like RISC-V's rem, x % 0 == x, so m == 0
+// gives i ** p (wrapping). Negative p gives 0.
 extern int64_t modpow(int64_t i, int64_t p, int64_t m);
+// Truncating division. Like RISC-V's div/rem, i / 0 gives q = -1, r = i and
+// INT64_MIN / -1 gives q = INT64_MIN, r = 0.
 extern void    divmod(int64_t *q, int64_t *r, int64_t i, int64_t m);
 extern double  sin(double);
 extern double  cos(double);
@@ -72,33 +177,42 @@ extern double  tanh(double);
 extern double  asinh(double);
 extern double  acosh(double);
 extern double  atanh(double);
-// erf
-// erfc
-// quadraticroots
-// horner
-// kepler
-//
+extern double  erf(double);
+extern double  erfc(double);
+// c[0] + c[1] x + ... + c[n - 1] x^(n - 1). Costs an extra instruction
+// per coefficient.
+extern double  horner(double *c, size_t n, double x);
+// Real roots of a x^2 + b x + c, smallest first, into roots[0..2]. Returns
+// how many there are. a == 0 is solved as linear.
+extern int     quadraticroots(double *roots, double a, double b, double c);
+// Eccentric anomaly E for mean anomaly M, solving M = E - e sin E for
+// 0 <= e < 1. E is in the same revolution as M. NaN for any other e.
+extern double  kepler(double M, double e);
+
+// Pointer arguments must point at mapped memory or the program faults.
+// Vectors are three packed doubles, so double[3] and vec3_t are
+// interchangeable. res may alias an argument.
 extern double dist33(double *a, double *b);
-extern void add33(double *res, double *a, double *b);
-extern void sub33(double *res, double *a, double *b);
-extern void muls3(double *res, double a, double *b);
-extern void divs3(double *res, double a, double *b);
+extern void add33(double *res, double *a, double *b);    // a + b
+extern void sub33(double *res, double *a, double *b);    // a - b
+extern void muls3(double *res, double a, double *b);     // a * b
+extern void divs3(double *res, double a, double *b);     // b / a
 extern void dot33(double *res, double *a, double *b);
 extern void cross33(double *res, double *a, double *b);
-extern double norm2(double *a, double *b);
-extern void normalize2(double *a);
-extern double norm3(double *a, double *b);
-extern void normalize3(double *a);
+extern double norm2(double *a);
+extern void normalize2(double *a);                       // zero stays zero
+extern double norm3(double *a);
+extern void normalize3(double *a);                       // zero stays zero
 extern void lerp33s(double *res, double *a, double *b, double t);
 
-extern double vdist33(vec3_t *res, vec3_t *a, vec3_t *b);
+extern double vdist33(vec3_t *a, vec3_t *b);
 extern void vadd33(vec3_t *res, vec3_t *a, vec3_t *b);
 extern void vsub33(vec3_t *res, vec3_t *a, vec3_t *b);
 extern void vmuls3(vec3_t *res, double a, vec3_t *b);
 extern void vdivs3(vec3_t *res, double a, vec3_t *b);
-extern void vdot33(vec3_t *res, vec3_t *a, vec3_t *b);
+extern void vdot33(double *res, vec3_t *a, vec3_t *b);
 extern void vcross33(vec3_t *res, vec3_t *a, vec3_t *b);
-extern double vnorm3(vec3_t *a, vec3_t *b);
+extern double vnorm3(vec3_t *a);
 extern void vnormalize3(vec3_t *a);
 extern void vlerp33s(vec3_t *res, vec3_t *a, vec3_t *b, double t);
 
@@ -138,4 +252,7 @@ struct __attribute__((packed, aligned(8))) celobjdat {
   char name[32];
 };
 
+// The host writes the table byte for byte; see bubbly_byter's celestial.rs
+_Static_assert(sizeof(struct celobjdat) == CELOBJDAT_SIZE, "celobjdat layout");
+
 extern struct celobjdat *celestials;
@@ -4,7 +4,9 @@ _start:
     .option push
     .option norelax
     .option pop
-    jal zero, main
+    jal ra, main
 _end:
+    # main's return value is already in a0
+    li a7, 0 # SYS_EXIT
     ecall
     .end
@@ -0,0 +1,380 @@
+// The game loop: move everything, then let every ship's computer have its
+// say. See riscv-mapped.md.
+//
+// A tick is, in this order:
+//
+// 1. The physics is stepped through the tick, with each ship's thrust as
+//    it was set at the end of the last one.
+// 2. Every ship's sensors and celestial table are refreshed from the new
+//    state.
+// 3. Every ship's computer is reset and run for its budget, in the order
+//    the ships were added.
+// 4. Every ship's throttles are read back and burnt, setting its thrust for
+//    the next tick.
+
+use std::collections::HashMap;
+
+use bubbly_byter::base_system::{BubblyByter, ExecutionOutcome};
+use bubbly_byter::celestial::{Celestial, KeplerianElements, MAX_CELESTIALS};
+use bubbly_byter::devices::{to_inertial, Beacon, Burn};
+use bubbly_byter::math::sub3;
+use gravsim::{BodyId, BodyKind, Catalog, NBodySimulation, SimObj, Vec3};
+
+pub const SECONDS_PER_DAY: f64 = 86400.0;
+
+fn to_array(v: &Vec3) -> [f64; 3] {
+    [v.x, v.y, v.z]
+}
+
+pub struct Ship {
+    pub name: String,
+    /// The ship's body in the simulation. Use `World::remove_ship` rather
+    /// than removing it from the simulation directly.
+    pub body: BodyId,
+    pub computer: BubblyByter,
+    /// Pitch, roll and yaw. Attitude isn't simulated yet, so this stays
+    /// wherever the host puts it and the attitude thrusters only burn
+    /// propellant.
+    pub orientation: [f64; 3],
+}
+
+/// A nav beacon riding along with a body, like a station or a moon. It
+/// goes quiet if the body is removed.
+pub struct NavBeacon {
+    pub frequency: f64,
+    pub body: BodyId,
+    /// m
+    pub range: f64,
+}
+
+/// A body from the catalog, listed in every ship's celestial table.
+pub struct CelestialBody {
+    pub name: String,
+    pub body: BodyId,
+    /// What its orbital elements are relative to: its parent in the
+    /// catalog, or for bodies without one, the catalog's heaviest body.
+    /// `None` for that body itself, which gets no elements.
+    pub parent: Option<BodyId>,
+    /// m
+    pub radius: f64,
+}
+
+/// What one ship did in a tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShipReport {
+    pub outcome: ExecutionOutcome,
+    pub log: Vec<String>,
+    pub log_dropped: usize,
+    pub burn: Burn,
+}
+
+/// What happened in a tick, with ship reports in the same order as
+/// `World::ships`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    /// Julian date at the end of the physics step, the time the ships saw.
+    pub date: f64,
+    pub ships: Vec<ShipReport>,
+}
+
+pub struct World {
+    pub sim: NBodySimulation,
+    pub ships: Vec<Ship>,
+    pub beacons: Vec<NavBeacon>,
+    /// In the order of the catalog the world was built from, which is the
+    /// order of the ships' celestial tables.
+    pub celestials: Vec<CelestialBody>,
+    /// Julian date at `sim.t() == 0`.
+    pub epoch: f64,
+    /// Seconds of simulation per tick. Stepped in `sim.dt()` steps, so it's
+    /// rounded to a multiple of it, and so are the ships' burns.
+    pub tick_length: f64,
+    /// Instructions each computer gets per tick.
+    pub budget: u64,
+}
+
+impl World {
+    /// A world of the bodies in `catalog`, as they are at Julian date
+    /// `epoch`. Set the simulation's step and integrator through `sim`.
+    pub fn new(catalog: &Catalog, epoch: f64, tick_length: f64, budget: u64) -> World {
+        let (sim, ids) = catalog.simulation();
+        let by_id: HashMap<&str, BodyId> = catalog
+            .bodies
+            .iter()
+            .map(|b| b.id.as_str())
+            .zip(ids.iter().copied())
+            .collect();
+        let primary = catalog
+            .bodies
+            .iter()
+            .zip(&ids)
+            .max_by(|a, b| a.0.mass.total_cmp(&b.0.mass))
+            .map(|(_, &id)| id);
+        let celestials = catalog
+            .bodies
+            .iter()
+            .zip(&ids)
+            .map(|(b, &id)| CelestialBody {
+                name: b.name.clone(),
+                body: id,
+                parent: match &b.parent {
+                    // The catalog checks every parent is in it.
+                    Some(parent) => Some(by_id[parent.as_str()]),
+                    None => primary.filter(|&p| p != id),
+                },
+                radius: b.radius,
+            })
+            .collect();
+
+        World {
+            sim,
+            ships: vec![],
+            beacons: vec![],
+            celestials,
+            epoch,
+            tick_length,
+            budget,
+        }
+    }
+
+    /// Launches a ship: `body` joins the simulation as a `BodyKind::Ship`,
+    /// whatever it was, and `computer` flies it from the next tick.
+    pub fn add_ship(&mut self, name: &str, mut body: SimObj, computer: BubblyByter) -> BodyId {
+        body.kind = BodyKind::Ship;
+        let id = self.sim.add_body(body);
+        self.ships.push(Ship {
+            name: name.to_string(),
+            body: id,
+            computer,
+            orientation: [0.0; 3],
+        });
+        id
+    }
+
+    /// Takes ship `index` out of the world, body and all.
+    pub fn remove_ship(&mut self, index: usize) -> Ship {
+        let ship = self.ships.remove(index);
+        self.sim.remove_body(ship.body);
+        ship
+    }
+
+    /// The simulation's current Julian date.
+    pub fn date(&self) -> f64 {
+        self.epoch + self.sim.t() / SECONDS_PER_DAY
+    }
+
+    /// The celestial table as the ships see it now: the celestials still
+    /// in the simulation, up to as many as fit.
+    pub fn celestial_table(&self) -> Vec<Celestial> {
+        let g = self.sim.gravitational_constant();
+        let date = self.date();
+        self.celestials
+            .iter()
+            .filter_map(|c| {
+                let body = self.sim.get(c.body)?;
+                let elements = match c.parent.and_then(|p| self.sim.get(p)) {
+                    Some(parent) => KeplerianElements::from_state_vector(
+                        sub3(to_array(&body.position), to_array(&parent.position)),
+                        sub3(to_array(&body.velocity), to_array(&parent.velocity)),
+                        g * parent.mass,
+                        date,
+                    ),
+                    None => KeplerianElements::default(),
+                };
+                Some(Celestial {
+                    elements,
+                    mass: body.mass,
+                    diameter: 2.0 * c.radius,
+                    name: c.name.clone(),
+                })
+            })
+            .take(MAX_CELESTIALS)
+            .collect()
+    }
+
+    /// Runs the physics for a tick, then every ship's computer. A ship
+    /// whose body has been taken out of `sim` directly, rather than with
+    /// `remove_ship`, has nothing left to fly and is dropped first.
+    pub fn tick(&mut self) -> Tick {
+        let sim = &self.sim;
+        self.ships.retain(|ship| sim.get(ship.body).is_some());
+
+        // What the physics steps for, and so how long the burns at the end
+        // of the last tick were for.
+        let length = self.sim.run_length(self.tick_length);
+        self.sim.run_for(length);
+
+        let date = self.date();
+        let celestials = self.celestial_table();
+        let beacons: Vec<Beacon> = self
+            .beacons
+            .iter()
+            .filter_map(|b| {
+                Some(Beacon {
+                    frequency: b.frequency,
+                    position: to_array(&self.sim.get(b.body)?.position),
+                    range: b.range,
+                })
+            })
+            .collect();
+
+        let mut reports = Vec::with_capacity(self.ships.len());
+        for ship in &mut self.ships {
+            let body = self.sim.get(ship.body).expect("orphaned ships are dropped");
+            let position = to_array(&body.position);
+            let velocity = to_array(&body.velocity);
+
+            {
+                let mut devices = ship.computer.devices_mut();
+                devices.chrono.current = date;
+                devices.irs.update(position, velocity, ship.orientation);
+                devices.adf.update(position, ship.orientation, &beacons);
+            }
+
+            ship.computer
+                .load_celestials(&celestials)
+                .expect("the celestial table is cut down to fit");
+            ship.computer.reset();
+            let outcome = ship.computer.execute_budget(self.budget);
+            let (log, log_dropped) = ship.computer.drain_log();
+
+            // Held through the next tick's physics, which runs as long as
+            // this one did, unless `sim.dt()` is changed in between.
+            let burn = ship.computer.devices_mut().burn(length);
+            let [x, y, z] = if length > 0.0 {
+                to_inertial(ship.orientation, burn.delta_v).map(|dv| dv / length)
+            } else {
+                [0.0; 3]
+            };
+            self.sim.set_thrust(ship.body, Vec3 { x, y, z });
+
+            reports.push(ShipReport {
+                outcome,
+                log,
+                log_dropped,
+                burn,
+            });
+        }
+
+        Tick {
+            date,
+            ships: reports,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bubbly_byter::devices::thrusters::STANDARD_GRAVITY;
+    use bubbly_byter::devices::{Fuel, Thruster};
+
+    /// Sets full x thrust and copies two numbers from the celestial table
+    /// into NVRAM; see testdata/tick.s.
+    const TICK_IMG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/tick.img");
+
+    const SUN_MASS: f64 = 1.989e30;
+    const AU: f64 = 1.496e11;
+
+    /// The Sun, and the Earth on a circular orbit around it.
+    fn world() -> World {
+        let v = (6.67408e-11 * SUN_MASS / AU).sqrt();
+        let catalog = Catalog::parse(&format!(
+            "{}\n\
+             SUN,soleil,,0,0,0,0,0,0,{},695508.0e3\n\
+             EARTH,terre,,{},0,0,0,{},0,5.97237e24,6371.0e3\n",
+            gravsim::catalog::HEADER,
+            SUN_MASS,
+            AU,
+            v
+        ))
+        .unwrap();
+        let mut world = World::new(&catalog, 2458214.5, 10.0, 1000);
+        // Ticks of 10 s run for 9.
+        world.sim.set_dt(3.0);
+        world
+    }
+
+    /// A ship far enough out that the Sun barely pulls on it, running
+    /// tick.img with 100 kg of propellant and a 1 kg s^-1 x thruster.
+    fn add_ship(world: &mut World) -> BodyId {
+        let mut computer = BubblyByter::new();
+        computer.load_kernel(TICK_IMG).unwrap();
+        {
+            let mut devices = computer.devices_mut();
+            devices.thrusters.position[0] = Thruster::new(1.0, 300.0);
+            devices.fuel = Fuel {
+                propellant: 100.0,
+                capacity: 100.0,
+                dry_mass: 1000.0,
+                ..Fuel::default()
+            };
+        }
+        let body = SimObj {
+            position: Vec3::new(0.0, 0.0, 1e15),
+            mass: 1100.0,
+            ..SimObj::default()
+        };
+        world.add_ship("test", body, computer)
+    }
+
+    #[test]
+    fn ships_are_always_test_particles() {
+        let mut world = world();
+        let id = add_ship(&mut world);
+        assert_eq!(world.sim.get(id).unwrap().kind, BodyKind::Ship);
+    }
+
+    #[test]
+    fn ships_burn_for_as_long_as_the_physics_runs() {
+        let mut world = world();
+        let id = add_ship(&mut world);
+
+        let tick = world.tick();
+        assert_eq!(world.sim.t(), 9.0);
+        let report = &tick.ships[0];
+        assert!(matches!(
+            report.outcome,
+            ExecutionOutcome::Exited { code: 0, .. }
+        ));
+        let dv = 300.0 * STANDARD_GRAVITY * (1100.0f64 / 1091.0).ln();
+        assert_eq!(report.burn.duration, 9.0);
+        assert_eq!(report.burn.propellant, 9.0);
+        assert!((report.burn.delta_v[0] - dv).abs() < 1e-9);
+
+        // The thrust is applied through the next tick's physics.
+        let before = world.sim.get(id).unwrap().velocity.clone();
+        world.tick();
+        let after = world.sim.get(id).unwrap().velocity.clone();
+        assert!((after.x - before.x - dv).abs() < 1e-6);
+        assert!((after.y - before.y).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ships_see_the_catalog_in_their_celestial_table() {
+        let mut world = world();
+        add_ship(&mut world);
+        assert_eq!(world.celestials.len(), 2);
+        assert_eq!(world.celestials[0].parent, None);
+        assert_eq!(world.celestials[1].parent, Some(world.celestials[0].body));
+
+        world.tick();
+        let nvram = world.ships[0].computer.save_nvram().unwrap();
+        let double = |i: usize| f64::from_le_bytes(nvram[i * 8..i * 8 + 8].try_into().unwrap());
+        assert_eq!(double(0), SUN_MASS);
+        assert!((double(1) - AU).abs() / AU < 1e-4, "a = {}", double(1));
+    }
+
+    #[test]
+    fn ships_whose_body_is_removed_are_dropped() {
+        let mut world = world();
+        let lost = add_ship(&mut world);
+        let kept = add_ship(&mut world);
+        world.sim.remove_body(lost);
+
+        let tick = world.tick();
+        assert_eq!(tick.ships.len(), 1);
+        assert_eq!(world.ships.len(), 1);
+        assert_eq!(world.ships[0].body, kept);
+    }
+}
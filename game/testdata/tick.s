@@ -0,0 +1,26 @@
+# Firmware for the tick test in lib.rs: sets the first position thruster
+# (x) to full, copies celestials[0].mass and celestials[1].ke.a into the
+# first two doubles of NVRAM, and exits.
+#
+# tick.img is this assembled with
+#   llvm-mc -triple=riscv64 -mattr=+m,+a,+f,+d -filetype=obj
+# and flattened with
+#   llvm-objcopy -O binary -j .text
+# to run from the start of RAM.
+
+    .section .text
+    li t0, 0x90000148           # thrusters.position[0].throttle
+    li t1, 0x3ff0000000000000   # 1.0
+    sd t1, 0(t0)
+
+    li t2, 0xa0000000           # NVRAM
+    li t0, 0x80001060           # celestials[0].mass
+    ld t1, 0(t0)
+    sd t1, 0(t2)
+    li t0, 0x80001098           # celestials[1].ke.a
+    ld t1, 0(t0)
+    sd t1, 8(t2)
+
+    li a0, 0
+    li a7, 0                    # SYS_EXIT
+    ecall
@@ -0,0 +1,129 @@
+// A single body and its rate of change.
+
+use std::ops::{Add, AddAssign, MulAssign};
+
+use crate::Vec3;
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct SimObjDerivative {
+    velocity: Vec3,
+    acceleration: Vec3,
+}
+
+impl SimObjDerivative {
+    pub fn step(&self, dt: f64) -> SimObj {
+        SimObj {
+            position: &self.velocity * dt,
+            velocity: &self.acceleration * dt,
+            ..SimObj::default()
+        }
+    }
+
+    pub fn apply_acceleration(&mut self, a: &Vec3) {
+        self.acceleration += a;
+    }
+}
+
+impl MulAssign<f64> for SimObjDerivative {
+    fn mul_assign(&mut self, rhs: f64) {
+        self.velocity *= rhs;
+        self.acceleration *= rhs;
+    }
+}
+
+impl Add for SimObjDerivative {
+    type Output = SimObjDerivative;
+    fn add(self, rhs: SimObjDerivative) -> Self::Output {
+        Self::Output {
+            velocity: self.velocity + rhs.velocity,
+            acceleration: self.acceleration + rhs.acceleration,
+        }
+    }
+}
+
+impl Add<&SimObjDerivative> for SimObjDerivative {
+    type Output = SimObjDerivative;
+    fn add(self, rhs: &SimObjDerivative) -> Self::Output {
+        Self::Output {
+            velocity: self.velocity + &rhs.velocity,
+            acceleration: self.acceleration + &rhs.acceleration,
+        }
+    }
+}
+
+impl Add<&SimObjDerivative> for &SimObjDerivative {
+    type Output = SimObjDerivative;
+    fn add(self, rhs: &SimObjDerivative) -> Self::Output {
+        Self::Output {
+            velocity: &self.velocity + &rhs.velocity,
+            acceleration: &self.acceleration + &rhs.acceleration,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BodyKind {
+    /// Pulls on and is pulled by every other massive body.
+    #[default]
+    Massive,
+    /// A test particle: feels the massive bodies' gravity but doesn't pull
+    /// on anything, so ships cost nothing to each other.
+    Ship,
+}
+
+/// A body in the simulation. Units are SI: meters, meters per second and
+/// kilograms.
+#[derive(Debug, Default, Clone)]
+pub struct SimObj {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub mass: f64,
+    pub kind: BodyKind,
+    /// Acceleration from the body's own engines, held constant through a
+    /// step.
+    pub thrust: Vec3,
+}
+
+// const G: f64 = 6.67408e-11;
+// const G: f64 = 1.0;
+
+impl SimObj {
+    #[allow(non_snake_case)]
+    pub fn stable_orbit(&self, G: f64, r: f64) -> f64 {
+        (G * self.mass / r).sqrt()
+    }
+
+    pub fn ship(position: Vec3, velocity: Vec3, mass: f64) -> SimObj {
+        SimObj {
+            position,
+            velocity,
+            mass,
+            kind: BodyKind::Ship,
+            thrust: Vec3::default(),
+        }
+    }
+
+    pub(crate) fn derivative(&self) -> SimObjDerivative {
+        SimObjDerivative {
+            velocity: self.velocity.clone(),
+            acceleration: self.thrust.clone(),
+        }
+    }
+}
+
+impl AddAssign<&SimObj> for SimObj {
+    fn add_assign(&mut self, rhs: &SimObj) {
+        self.position += &rhs.position;
+        self.velocity += &rhs.velocity;
+    }
+}
+
+impl Add<&SimObj> for &SimObj {
+    type Output = SimObj;
+    fn add(self, rhs: &SimObj) -> Self::Output {
+        let mut new = self.clone();
+        new.position += &rhs.position;
+        new.velocity += &rhs.velocity;
+        new
+    }
+}
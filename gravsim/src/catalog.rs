@@ -0,0 +1,279 @@
+// Initial conditions, one body per row of a CSV file. See bodies.csv:
+//
+//     # comments and blank lines are skipped
+//     name,id,parent,x,y,z,vx,vy,vz,mass,radius
+//     SUN,soleil,,181791441.0,983586429.0,-15862871.7,-11.247433,7.54873918,0.26870813,1.989e30,695508.0e3
+//     MOON,lune,terre,...
+//
+// Units are SI. `parent` is the `id` of the body this one orbits, or empty.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{BodyId, NBodySimulation, SimObj, Vec3};
+
+pub const HEADER: &str = "name,id,parent,x,y,z,vx,vy,vz,mass,radius";
+const COLUMNS: [&str; 11] = [
+    "name", "id", "parent", "x", "y", "z", "vx", "vy", "vz", "mass", "radius",
+];
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(io::Error),
+    /// `row` is the 1-based line in the file.
+    Parse {
+        row: usize,
+        message: String,
+    },
+}
+
+impl From<io::Error> for CatalogError {
+    fn from(e: io::Error) -> Self {
+        CatalogError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BodyRecord {
+    pub name: String,
+    pub id: String,
+    pub parent: Option<String>,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// kg
+    pub mass: f64,
+    /// Mean radius, m.
+    pub radius: f64,
+}
+
+impl BodyRecord {
+    pub fn to_sim_obj(&self) -> SimObj {
+        SimObj {
+            position: self.position.clone(),
+            velocity: self.velocity.clone(),
+            mass: self.mass,
+            ..SimObj::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Catalog {
+    pub bodies: Vec<BodyRecord>,
+}
+
+impl Catalog {
+    /// Parses and checks a catalog: every row has all the columns, numbers
+    /// are finite, masses are positive, radii aren't negative, ids are
+    /// unique and every parent is another body in the file.
+    pub fn parse(text: &str) -> Result<Catalog, CatalogError> {
+        let mut bodies = vec![];
+        // id to the row it was on
+        let mut rows: HashMap<String, usize> = HashMap::new();
+        let mut seen_header = false;
+
+        for (i, line) in text.lines().enumerate() {
+            let row = i + 1;
+            let err = |message: String| CatalogError::Parse { row, message };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if !seen_header {
+                if line != HEADER {
+                    return Err(err(format!("expected the header `{}`", HEADER)));
+                }
+                seen_header = true;
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != COLUMNS.len() {
+                return Err(err(format!(
+                    "expected {} columns, found {}",
+                    COLUMNS.len(),
+                    fields.len()
+                )));
+            }
+
+            let mut numbers = [0.0; 8];
+            for (n, (column, field)) in numbers
+                .iter_mut()
+                .zip(COLUMNS[3..].iter().zip(&fields[3..]))
+            {
+                *n = field
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| n.is_finite())
+                    .ok_or_else(|| err(format!("{}: `{}` isn't a number", column, field)))?;
+            }
+            let [x, y, z, vx, vy, vz, mass, radius] = numbers;
+
+            let (name, id, parent) = (fields[0], fields[1], fields[2]);
+            if name.is_empty() || id.is_empty() {
+                return Err(err("name and id can't be empty".to_string()));
+            }
+            if let Some(first) = rows.insert(id.to_string(), row) {
+                return Err(err(format!("id `{}` is already used on row {}", id, first)));
+            }
+            if mass <= 0.0 {
+                return Err(err(format!("mass: {} isn't positive", mass)));
+            }
+            if radius < 0.0 {
+                return Err(err(format!("radius: {} is negative", radius)));
+            }
+
+            bodies.push((
+                row,
+                BodyRecord {
+                    name: name.to_string(),
+                    id: id.to_string(),
+                    parent: (!parent.is_empty()).then(|| parent.to_string()),
+                    position: Vec3::new(x, y, z),
+                    velocity: Vec3::new(vx, vy, vz),
+                    mass,
+                    radius,
+                },
+            ));
+        }
+
+        // Parents can come after their children, so they're checked once
+        // every id is known.
+        for (row, body) in &bodies {
+            if let Some(parent) = &body.parent {
+                if parent == &body.id || !rows.contains_key(parent) {
+                    return Err(CatalogError::Parse {
+                        row: *row,
+                        message: format!("parent `{}` isn't another body", parent),
+                    });
+                }
+            }
+        }
+
+        Ok(Catalog {
+            bodies: bodies.into_iter().map(|(_, b)| b).collect(),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Catalog, CatalogError> {
+        Catalog::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&BodyRecord> {
+        self.bodies.iter().find(|b| b.id == id)
+    }
+
+    /// A simulation of every body, and their ids in the same order as
+    /// `bodies`.
+    pub fn simulation(&self) -> (NBodySimulation, Vec<BodyId>) {
+        let mut sim = NBodySimulation::new();
+        let ids = self
+            .bodies
+            .iter()
+            .map(|b| sim.add_body(b.to_sim_obj()))
+            .collect();
+        (sim, ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUN: &str = "SUN,soleil,,0,0,0,0,0,0,1.989e30,695508.0e3";
+    const EARTH: &str = "EARTH,terre,,1.496e11,0,0,0,29784.7,0,5.97237e24,6371.0e3";
+
+    /// The row and message `rows`, after the header, are rejected with.
+    fn rejection(rows: &[&str]) -> (usize, String) {
+        let text = format!("{}\n{}\n", HEADER, rows.join("\n"));
+        match Catalog::parse(&text) {
+            Err(CatalogError::Parse { row, message }) => (row, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_the_shipped_bodies() {
+        let catalog = Catalog::load(concat!(env!("CARGO_MANIFEST_DIR"), "/bodies.csv")).unwrap();
+        assert_eq!(catalog.bodies.len(), 171);
+        assert_eq!(catalog.bodies[0].id, "soleil");
+        assert_eq!(catalog.bodies[0].parent, None);
+        assert_eq!(
+            catalog.find("lune").unwrap().parent.as_deref(),
+            Some("terre")
+        );
+        assert!(catalog.find("davida").is_some());
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let text = format!("# bodies\n\n{}\n{}\n\n# more\n{}\n", HEADER, SUN, EARTH);
+        let catalog = Catalog::parse(&text).unwrap();
+        assert_eq!(catalog.bodies.len(), 2);
+        assert_eq!(catalog.bodies[1].velocity.y, 29784.7);
+    }
+
+    #[test]
+    fn rejects_a_duplicate_id() {
+        let again = "EARTH AGAIN,terre,,0,1.496e11,0,29784.7,0,0,5.97237e24,6371.0e3";
+        let (row, message) = rejection(&[SUN, EARTH, again]);
+        assert_eq!(row, 4);
+        assert_eq!(message, "id `terre` is already used on row 3");
+    }
+
+    #[test]
+    fn rejects_a_non_finite_mass() {
+        for mass in ["inf", "-inf", "NaN"] {
+            let earth = EARTH.replace("5.97237e24", mass);
+            let (row, message) = rejection(&[SUN, &earth]);
+            assert_eq!(row, 3);
+            assert_eq!(message, format!("mass: `{}` isn't a number", mass));
+        }
+    }
+
+    #[test]
+    fn rejects_a_missing_column() {
+        let (row, message) = rejection(&[SUN, "EARTH,terre,,1.496e11,0,0,0,29784.7,0,5.97237e24"]);
+        assert_eq!(row, 3);
+        assert_eq!(message, "expected 11 columns, found 10");
+    }
+
+    #[test]
+    fn rejects_a_value_of_the_wrong_kind() {
+        let earth = EARTH.replace("29784.7", "fast");
+        let (row, message) = rejection(&[SUN, &earth]);
+        assert_eq!(row, 3);
+        assert_eq!(message, "vy: `fast` isn't a number");
+    }
+
+    #[test]
+    fn rejects_a_mass_that_isnt_positive() {
+        let earth = EARTH.replace("5.97237e24", "0");
+        assert_eq!(rejection(&[SUN, &earth]).1, "mass: 0 isn't positive");
+    }
+
+    #[test]
+    fn rejects_an_unknown_parent() {
+        let moon = "MOON,lune,terre,0,0,0,0,0,0,7.346e22,1737.0e3";
+        assert_eq!(
+            rejection(&[moon, SUN]),
+            (2, "parent `terre` isn't another body".to_string())
+        );
+        // Parents can come later in the file.
+        let text = format!("{}\n{}\n{}\n", HEADER, moon, EARTH);
+        assert!(Catalog::parse(&text).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_missing_header() {
+        match Catalog::parse(&format!("# no header\n{}\n", SUN)) {
+            Err(CatalogError::Parse { row: 2, message }) => {
+                assert!(message.starts_with("expected the header"))
+            }
+            other => panic!("expected a parse error on row 2, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// An N-body gravity simulation: planets, moons and small bodies pulling on
+// each other, and ships that feel their pull and fly under their own
+// thrust.
+
+mod body;
+pub mod catalog;
+mod simulation;
+pub mod spice;
+mod vec3;
+
+pub use body::{BodyKind, SimObj};
+pub use catalog::{BodyRecord, Catalog, CatalogError};
+pub use simulation::{BodyId, Integrator, NBodySimulation};
+pub use vec3::Vec3;
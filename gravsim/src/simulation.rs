@@ -0,0 +1,699 @@
+// The simulation itself.
+
+use std::default::Default;
+use std::fmt;
+use std::ops::{Add, MulAssign};
+
+use crate::body::{BodyKind, SimObj, SimObjDerivative};
+use crate::Vec3;
+
+/// Names a body for as long as it's in the simulation. Ids are never
+/// reused, so one can't come to mean a different body after a removal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BodyId(u64);
+
+impl fmt::Display for BodyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct NBodySimulationDerivative {
+    bodies: Vec<SimObjDerivative>,
+}
+
+impl NBodySimulationDerivative {
+    pub fn step(&self, dt: f64) -> NBodySimulation {
+        NBodySimulation {
+            bodies: self.bodies.iter().map(|b| b.step(dt)).collect(),
+            ..NBodySimulation::default()
+        }
+    }
+}
+
+impl MulAssign<f64> for NBodySimulationDerivative {
+    fn mul_assign(&mut self, rhs: f64) {
+        for i in self.bodies.iter_mut() {
+            *i *= rhs;
+        }
+    }
+}
+
+impl Add<NBodySimulationDerivative> for NBodySimulationDerivative {
+    type Output = NBodySimulationDerivative;
+    fn add(self, rhs: NBodySimulationDerivative) -> Self::Output {
+        NBodySimulationDerivative {
+            bodies: self
+                .bodies
+                .iter()
+                .zip(&rhs.bodies)
+                .map(|(a, b)| a + b)
+                .collect(),
+        }
+    }
+}
+
+/// How `NBodySimulation` steps.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Integrator {
+    /// Fixed steps of `dt`.
+    #[default]
+    Rk4,
+    /// Dormand–Prince 5(4): each step's error is estimated from the
+    /// difference between a 5th and a 4th order solution, and `dt` grows
+    /// or shrinks to keep every component of it within
+    /// `tolerance + relative_tolerance * max(|y|, |y_new|)`, with the
+    /// position and velocity tolerances in m and m/s. Steps that miss are
+    /// retried shorter.
+    DormandPrince {
+        position_tolerance: f64,
+        velocity_tolerance: f64,
+        relative_tolerance: f64,
+    },
+}
+
+// The Dormand–Prince tableau. Row i of A gives stage i + 1's input from the
+// stages before it; the last row is also the 5th order solution.
+const DP_A: [[f64; 6]; 6] = [
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+/// The 5th order weights less the 4th's, over all seven stages.
+const DP_E: [f64; 7] = [
+    35.0 / 384.0 - 5179.0 / 57600.0,
+    0.0,
+    500.0 / 1113.0 - 7571.0 / 16695.0,
+    125.0 / 192.0 - 393.0 / 640.0,
+    -2187.0 / 6784.0 + 92097.0 / 339200.0,
+    11.0 / 84.0 - 187.0 / 2100.0,
+    -1.0 / 40.0,
+];
+
+/// `Σ weights[i] * ks[i]`.
+fn weighted(ks: &[NBodySimulationDerivative], weights: &[f64]) -> NBodySimulationDerivative {
+    let mut sum = NBodySimulationDerivative {
+        bodies: vec![SimObjDerivative::default(); ks[0].bodies.len()],
+    };
+    for (k, &w) in ks.iter().zip(weights) {
+        if w != 0.0 {
+            let mut k = k.clone();
+            k *= w;
+            sum = sum + k;
+        }
+    }
+    sum
+}
+
+/// The worst of `error`'s components over
+/// `tolerance + relative_tolerance * max(|y|, |y_new|)`.
+fn scaled_error(
+    error: &Vec3,
+    y: &Vec3,
+    y_new: &Vec3,
+    tolerance: f64,
+    relative_tolerance: f64,
+) -> f64 {
+    [
+        (error.x, y.x, y_new.x),
+        (error.y, y.y, y_new.y),
+        (error.z, y.z, y_new.z),
+    ]
+    .iter()
+    .map(|&(e, a, b)| e.abs() / (tolerance + relative_tolerance * a.abs().max(b.abs())))
+    .fold(0.0, f64::max)
+}
+
+/// Bodies under each other's gravity, stepped by an `Integrator`. Bodies can come
+/// and go as it runs; each is kept under the `BodyId` it was added with,
+/// and the bodies stay in the order they were added.
+#[derive(Debug, Clone)]
+#[allow(non_snake_case)]
+pub struct NBodySimulation {
+    bodies: Vec<SimObj>,
+    /// `ids[i]` is `bodies[i]`'s. Ids are handed out in increasing order
+    /// and removals keep the order, so this is always sorted.
+    ids: Vec<BodyId>,
+    next_id: u64,
+    dt: f64,
+    G: f64,
+    t: f64,
+    integrator: Integrator,
+    /// The derivative at the current state, left by a Dormand–Prince step:
+    /// its last stage is the next step's first. Cleared by anything that
+    /// changes the bodies or G.
+    fsal: Option<NBodySimulationDerivative>,
+}
+
+impl NBodySimulation {
+    /// `self` moved on by the change `rhs`, which must have one body for
+    /// every one of `self`'s.
+    fn offset(&self, rhs: &NBodySimulation) -> NBodySimulation {
+        let mut new = self.clone();
+        for (b, d) in new.bodies.iter_mut().zip(&rhs.bodies) {
+            *b += d;
+        }
+        new
+    }
+}
+
+impl Add<NBodySimulation> for NBodySimulation {
+    type Output = NBodySimulation;
+    fn add(self, rhs: NBodySimulation) -> Self::Output {
+        self.offset(&rhs)
+    }
+}
+
+impl Add<&NBodySimulation> for NBodySimulation {
+    type Output = NBodySimulation;
+    fn add(self, rhs: &NBodySimulation) -> Self::Output {
+        self.offset(rhs)
+    }
+}
+
+impl Add<NBodySimulation> for &NBodySimulation {
+    type Output = NBodySimulation;
+    fn add(self, rhs: NBodySimulation) -> Self::Output {
+        self.offset(&rhs)
+    }
+}
+
+impl Add<&NBodySimulation> for &NBodySimulation {
+    type Output = NBodySimulation;
+    fn add(self, rhs: &NBodySimulation) -> Self::Output {
+        self.offset(rhs)
+    }
+}
+
+impl Default for NBodySimulation {
+    fn default() -> Self {
+        NBodySimulation {
+            bodies: vec![],
+            ids: vec![],
+            next_id: 0,
+            G: 6.67408e-11,
+            dt: 1.0,
+            t: 0.0,
+            integrator: Integrator::Rk4,
+            fsal: None,
+        }
+    }
+}
+
+impl NBodySimulation {
+    pub fn new() -> NBodySimulation {
+        NBodySimulation::default()
+    }
+
+    /// Seconds since the start of the simulation.
+    pub fn t(&self) -> f64 {
+        self.t
+    }
+
+    /// Seconds per step. With an adaptive integrator this is the next
+    /// step it'll try, and what's set is only the first.
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    pub fn set_dt(&mut self, dt: f64) {
+        self.dt = dt;
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// G, in m^3 kg^-1 s^-2 unless the bodies are in some other units.
+    pub fn gravitational_constant(&self) -> f64 {
+        self.G
+    }
+
+    pub fn set_gravitational_constant(&mut self, g: f64) {
+        self.fsal = None;
+        self.G = g;
+    }
+
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+
+    /// Every body, in the order they were added. `ids()` is in the same
+    /// order.
+    pub fn bodies(&self) -> &[SimObj] {
+        &self.bodies
+    }
+
+    pub fn ids(&self) -> &[BodyId] {
+        &self.ids
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BodyId, &SimObj)> {
+        self.ids.iter().copied().zip(&self.bodies)
+    }
+
+    fn index(&self, id: BodyId) -> Option<usize> {
+        self.ids.binary_search(&id).ok()
+    }
+
+    pub fn contains(&self, id: BodyId) -> bool {
+        self.index(id).is_some()
+    }
+
+    /// The body with `id`, or `None` if it's been removed.
+    pub fn get(&self, id: BodyId) -> Option<&SimObj> {
+        self.index(id).map(|i| &self.bodies[i])
+    }
+
+    pub fn get_mut(&mut self, id: BodyId) -> Option<&mut SimObj> {
+        self.fsal = None;
+        self.index(id).map(|i| &mut self.bodies[i])
+    }
+
+    /// Puts `body` into the simulation from the next step on.
+    pub fn add_body(&mut self, body: SimObj) -> BodyId {
+        let id = BodyId(self.next_id);
+        self.next_id += 1;
+        self.fsal = None;
+        self.bodies.push(body);
+        self.ids.push(id);
+        id
+    }
+
+    /// Takes the body with `id` out of the simulation. Its id isn't
+    /// reused.
+    pub fn remove_body(&mut self, id: BodyId) -> Option<SimObj> {
+        let i = self.index(id)?;
+        self.fsal = None;
+        self.ids.remove(i);
+        Some(self.bodies.remove(i))
+    }
+
+    /// Whole RK4 steps in `duration`, to the nearest.
+    fn rk4_steps(&self, duration: f64) -> u64 {
+        (duration / self.dt).round() as u64
+    }
+
+    /// How long `run_for(duration)` actually steps for, so anything held
+    /// through it, like a burn, can last exactly as long.
+    pub fn run_length(&self, duration: f64) -> f64 {
+        match self.integrator {
+            Integrator::Rk4 => self.rk4_steps(duration) as f64 * self.dt,
+            Integrator::DormandPrince { .. } => duration.max(0.0),
+        }
+    }
+
+    /// Steps for `duration` seconds. RK4 rounds it to a whole number of
+    /// steps; adaptive integrators shorten the last step to land on it.
+    pub fn run_for(&mut self, duration: f64) {
+        match self.integrator {
+            Integrator::Rk4 => {
+                for _ in 0..self.rk4_steps(duration) {
+                    self.rk4();
+                }
+            }
+            Integrator::DormandPrince { .. } => {
+                let end = self.t + duration;
+                while self.t < end {
+                    let left = end - self.t;
+                    if self.dormand_prince(left) == left {
+                        self.t = end;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Takes one step.
+    pub fn update(&mut self) {
+        match self.integrator {
+            Integrator::Rk4 => self.rk4(),
+            Integrator::DormandPrince { .. } => {
+                self.dormand_prince(f64::INFINITY);
+            }
+        }
+    }
+
+    fn rk4(&mut self) {
+        self.fsal = None;
+        let k1 = self.derivative();
+        let mut k2 = (&*self + k1.step(self.dt / 2.0)).derivative();
+        let mut k3 = (&*self + k2.step(self.dt / 2.0)).derivative();
+        let k4 = (&*self + k3.step(self.dt)).derivative();
+
+        k2 *= 2.0;
+        k3 *= 2.0;
+
+        let mut d = k1 + k2 + k3 + k4;
+        d *= 1.0 / 6.0;
+        let d = d.step(self.dt);
+        for (b, d) in self.bodies.iter_mut().zip(&d.bodies) {
+            *b += d;
+        }
+
+        self.t += self.dt;
+    }
+
+    /// Takes one Dormand–Prince step of at most `max` seconds, retrying
+    /// shorter until it's within the tolerances. Returns the step taken.
+    fn dormand_prince(&mut self, max: f64) -> f64 {
+        let Integrator::DormandPrince {
+            position_tolerance,
+            velocity_tolerance,
+            relative_tolerance,
+        } = self.integrator
+        else {
+            return 0.0;
+        };
+
+        // Taken out so the stages don't clone it along with the bodies.
+        let k1 = match self.fsal.take() {
+            Some(k1) => k1,
+            None => self.derivative(),
+        };
+        let clamped = self.dt > max;
+        let mut h = self.dt.min(max);
+        loop {
+            let mut ks = vec![k1.clone()];
+            for a in &DP_A {
+                let k = (&*self + weighted(&ks, a).step(h)).derivative();
+                ks.push(k);
+            }
+            let next = &*self + weighted(&ks, &DP_A[5]).step(h);
+
+            // The error relative to the tolerances, for the worst component.
+            let error = weighted(&ks, &DP_E)
+                .step(h)
+                .bodies
+                .iter()
+                .zip(self.bodies.iter().zip(&next.bodies))
+                .map(|(e, (y, y_new))| {
+                    scaled_error(
+                        &e.position,
+                        &y.position,
+                        &y_new.position,
+                        position_tolerance,
+                        relative_tolerance,
+                    )
+                    .max(scaled_error(
+                        &e.velocity,
+                        &y.velocity,
+                        &y_new.velocity,
+                        velocity_tolerance,
+                        relative_tolerance,
+                    ))
+                })
+                .fold(0.0, f64::max);
+            let factor = if error == 0.0 {
+                5.0
+            } else {
+                (0.9 * error.powf(-0.2)).clamp(0.2, 5.0)
+            };
+
+            // Give up on the tolerances rather than stall once the step is
+            // too short to move t.
+            if error <= 1.0 || self.t + h == self.t {
+                self.bodies = next.bodies;
+                self.t += h;
+                // The last stage was taken at the new state.
+                self.fsal = ks.pop();
+                // A step cut short to land on `max` says nothing about
+                // whether `dt` was too long.
+                if !(clamped && factor >= 1.0) {
+                    self.dt = h * factor;
+                }
+                return h;
+            }
+            h *= factor;
+        }
+    }
+
+    fn derivative(&mut self) -> NBodySimulationDerivative {
+        let mut d = NBodySimulationDerivative {
+            bodies: self.bodies.iter().map(SimObj::derivative).collect(),
+        };
+        let (massive, ships): (Vec<usize>, Vec<usize>) =
+            (0..self.bodies.len()).partition(|&i| self.bodies[i].kind == BodyKind::Massive);
+        for (n, &i) in massive.iter().enumerate() {
+            for &j in &massive[n + 1..] {
+                if let Ok([a, b]) = self.bodies.get_disjoint_mut([i, j]) {
+                    let r_vec = &a.position - &b.position;
+                    let r = r_vec.l2_norm();
+                    let f = r_vec * self.G * a.mass * b.mass / r.powi(3);
+                    if let Ok([ad, bd]) = d.bodies.get_disjoint_mut([i, j]) {
+                        ad.apply_acceleration(&(-&f / a.mass));
+                        bd.apply_acceleration(&(&f / b.mass));
+                    }
+                }
+            }
+        }
+        // Ships only feel the massive bodies, so they're O(ships * massive)
+        // rather than adding to the pairs above.
+        for &i in &ships {
+            for &j in &massive {
+                let r_vec = &self.bodies[j].position - &self.bodies[i].position;
+                let r = r_vec.l2_norm();
+                let a = r_vec * self.G * self.bodies[j].mass / r.powi(3);
+                d.bodies[i].apply_acceleration(&a);
+            }
+        }
+        d
+    }
+
+    /// Sets the acceleration the body's engines give it until it's set
+    /// again, normally once a tick from its thrusters. Returns false if
+    /// there's no such body.
+    pub fn set_thrust(&mut self, id: BodyId, acceleration: Vec3) -> bool {
+        match self.get_mut(id) {
+            Some(b) => {
+                b.thrust = acceleration;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Massive bodies from rows of `x, y, z, vx, vy, vz, mass`, with ids
+    /// in row order.
+    pub fn from_matrix(m: &[[f64; 7]]) -> NBodySimulation {
+        let mut sim = NBodySimulation::default();
+
+        for row in m {
+            sim.add_body(SimObj {
+                position: Vec3::new(row[0], row[1], row[2]),
+                velocity: Vec3::new(row[3], row[4], row[5]),
+                mass: row[6],
+                ..SimObj::default()
+            });
+        }
+
+        sim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    const SUN_MASS: f64 = 2e30;
+    const R: f64 = 1.5e11;
+
+    /// A test particle on a circular orbit of radius `R` around a Sun that
+    /// it doesn't pull on, so the orbit stays exactly circular. Returns it
+    /// and the period.
+    fn circular_orbit(integrator: Integrator, dt: f64) -> (NBodySimulation, f64) {
+        let mut sim = NBodySimulation::new();
+        let mu = sim.gravitational_constant() * SUN_MASS;
+        sim.add_body(SimObj {
+            mass: SUN_MASS,
+            ..SimObj::default()
+        });
+        sim.add_body(SimObj::ship(
+            Vec3::new(R, 0.0, 0.0),
+            Vec3::new(0.0, (mu / R).sqrt(), 0.0),
+            1.0,
+        ));
+        sim.set_integrator(integrator);
+        sim.set_dt(dt);
+        (sim, TAU * (R.powi(3) / mu).sqrt())
+    }
+
+    #[test]
+    fn removed_bodies_leave_the_other_ids_alone() {
+        let mut sim = NBodySimulation::from_matrix(&[
+            [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0],
+            [2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0],
+        ]);
+        let ids = sim.ids().to_vec();
+
+        let removed = sim.remove_body(ids[1]).unwrap();
+        assert_eq!(removed.mass, 2.0);
+        assert!(sim.get(ids[1]).is_none());
+        assert!(!sim.contains(ids[1]));
+        assert!(sim.remove_body(ids[1]).is_none());
+        assert_eq!(sim.len(), 2);
+        assert_eq!(sim.get(ids[0]).unwrap().mass, 1.0);
+        assert_eq!(sim.get(ids[2]).unwrap().mass, 3.0);
+
+        // Ids aren't reused, and the survivors keep theirs as the
+        // simulation runs.
+        let added = sim.add_body(SimObj {
+            mass: 4.0,
+            ..SimObj::default()
+        });
+        assert!(!ids.contains(&added));
+        sim.run_for(10.0);
+        assert_eq!(sim.ids(), [ids[0], ids[2], added]);
+        assert_eq!(sim.get(ids[2]).unwrap().mass, 3.0);
+        assert_eq!(sim.get(added).unwrap().mass, 4.0);
+    }
+
+    #[test]
+    fn ships_feel_gravity_but_dont_pull() {
+        let mut sim = NBodySimulation::new();
+        let planet = sim.add_body(SimObj {
+            mass: 1e24,
+            ..SimObj::default()
+        });
+        let a = sim.add_body(SimObj::ship(Vec3::new(1e7, 0.0, 0.0), Vec3::default(), 1e6));
+        let b = sim.add_body(SimObj::ship(Vec3::new(0.0, 1e7, 0.0), Vec3::default(), 1e6));
+        sim.update();
+
+        // Nothing pulls on the planet, and the ships don't pull on each
+        // other.
+        assert_eq!(sim.get(planet).unwrap().velocity.l2_norm(), 0.0);
+        assert_eq!(sim.get(a).unwrap().velocity.y, 0.0);
+        assert_eq!(sim.get(b).unwrap().velocity.x, 0.0);
+        assert!(sim.get(a).unwrap().velocity.x < 0.0);
+        assert!(sim.get(b).unwrap().velocity.y < 0.0);
+    }
+
+    #[test]
+    fn thrust_accelerates_a_ship() {
+        let mut sim = NBodySimulation::new();
+        let ship = sim.add_body(SimObj::ship(Vec3::default(), Vec3::default(), 1e6));
+        assert!(sim.set_thrust(ship, Vec3::new(2.0, 0.0, 0.0)));
+        sim.update();
+        assert!((sim.get(ship).unwrap().velocity.x - 2.0).abs() < 1e-12);
+        assert!((sim.get(ship).unwrap().position.x - 1.0).abs() < 1e-12);
+    }
+
+    fn dormand_prince() -> Integrator {
+        Integrator::DormandPrince {
+            position_tolerance: 1.0,
+            velocity_tolerance: 1e-6,
+            relative_tolerance: 1e-10,
+        }
+    }
+
+    /// How far the particle is from where it should be at `t`.
+    fn orbit_error(sim: &NBodySimulation, period: f64) -> f64 {
+        let angle = TAU * sim.t() / period;
+        let expected = Vec3::new(R * angle.cos(), R * angle.sin(), 0.0);
+        (&sim.bodies()[1].position - &expected).l2_norm()
+    }
+
+    #[test]
+    fn dormand_prince_agrees_with_rk4() {
+        let (mut rk4, period) = circular_orbit(Integrator::Rk4, 600.0);
+        let (mut dp, _) = circular_orbit(dormand_prince(), 600.0);
+        rk4.run_for(period / 4.0);
+        dp.run_for(period / 4.0);
+
+        assert_eq!(dp.t(), period / 4.0);
+        assert!((rk4.t() - period / 4.0).abs() <= 300.0);
+        assert!(
+            orbit_error(&rk4, period) < 1e3,
+            "{}",
+            orbit_error(&rk4, period)
+        );
+        assert!(
+            orbit_error(&dp, period) < 1e3,
+            "{}",
+            orbit_error(&dp, period)
+        );
+        // And it got there in far longer steps.
+        assert!(dp.dt() > 10.0 * 600.0, "{}", dp.dt());
+    }
+
+    #[test]
+    fn steps_that_miss_the_tolerances_are_retried_shorter() {
+        // A step of a third of an orbit is far off, so it's cut down until
+        // it's close enough.
+        let (mut sim, period) = circular_orbit(dormand_prince(), 1e7);
+        sim.update();
+        assert!(sim.t() < 1e7 / 5.0, "{}", sim.t());
+        assert!(
+            orbit_error(&sim, period) < 10.0,
+            "{}",
+            orbit_error(&sim, period)
+        );
+    }
+
+    #[test]
+    fn the_last_stage_starts_the_next_step() {
+        let (mut reused, _) = circular_orbit(dormand_prince(), 600.0);
+        reused.update();
+        assert!(reused.fsal.is_some());
+        let mut fresh = reused.clone();
+        fresh.fsal = None;
+        reused.update();
+        fresh.update();
+        assert_eq!(reused.t(), fresh.t());
+        assert_eq!(reused.bodies()[1].position.x, fresh.bodies()[1].position.x);
+
+        // Anything that changes what pulls on what starts over.
+        let id = reused.ids()[1];
+        reused.set_thrust(id, Vec3::new(1.0, 0.0, 0.0));
+        assert!(reused.fsal.is_none());
+        reused.update();
+        reused.add_body(SimObj::default());
+        assert!(reused.fsal.is_none());
+    }
+
+    #[test]
+    fn errors_are_scaled_by_the_larger_state() {
+        let error = Vec3::new(1.0, 0.5, 0.0);
+        let y = Vec3::new(100.0, 0.0, 0.0);
+        let y_new = Vec3::new(-300.0, 0.0, 0.0);
+        // x: 1 / (1 + 0.01 * 300), y: 0.5 / 1
+        assert_eq!(scaled_error(&error, &y, &y_new, 1.0, 0.01), 0.5);
+        let error = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(scaled_error(&error, &y, &y_new, 0.0, 0.01), 1.0 / 3.0);
+    }
+}
@@ -0,0 +1,242 @@
+// The Double precision Array File format that binary kernels are written
+// in. A file record says how big a summary is and where the first summary
+// record is; summary records are chained and each holds some summaries,
+// which are `nd` doubles then `ni` integers describing an array of doubles
+// somewhere in the file. Addresses count doubles from 1.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::SpiceError;
+
+const RECORD_LEN: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub doubles: Vec<f64>,
+    pub ints: Vec<i32>,
+}
+
+#[derive(Debug)]
+pub struct Daf {
+    file: File,
+    /// Bytes.
+    len: u64,
+    little_endian: bool,
+    /// "DAF/SPK", "DAF/PCK", ...
+    pub kind: String,
+    pub summaries: Vec<Summary>,
+}
+
+impl Daf {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Daf, SpiceError> {
+        let file = File::open(path)?;
+        let err = |message: &str| SpiceError::Daf(message.to_string());
+
+        let mut record = [0u8; RECORD_LEN];
+        (&file).read_exact(&mut record)?;
+        let kind = String::from_utf8_lossy(&record[0..8]).trim().to_string();
+        if !kind.starts_with("DAF/") {
+            return Err(err("not a DAF file"));
+        }
+        let little_endian = match &record[88..96] {
+            b"LTL-IEEE" => true,
+            b"BIG-IEEE" => false,
+            _ => return Err(err("not an IEEE file")),
+        };
+
+        let len = file.metadata()?.len();
+        let mut daf = Daf {
+            file,
+            len,
+            little_endian,
+            kind,
+            summaries: vec![],
+        };
+        let int = |i: usize| daf.int(record[i..i + 4].try_into().unwrap());
+        let (nd, ni, fward) = (int(8) as usize, int(12) as usize, int(76));
+        if nd > 124 || !(2..=250).contains(&ni) {
+            return Err(err("bad summary size"));
+        }
+        let summary_len = nd + ni.div_ceil(2);
+
+        // The summary records are a linked list; a loop would be a broken
+        // file, so give up on one after as many records as the file has.
+        let records = daf.len / RECORD_LEN as u64;
+        let mut next = fward as u64;
+        let mut seen = 0;
+        let mut summaries = vec![];
+        while next != 0 {
+            seen += 1;
+            if next > records || seen > records {
+                return Err(err("summary records run off the file"));
+            }
+            let record = daf.read_bytes((next - 1) * RECORD_LEN as u64, RECORD_LEN)?;
+            let double = |i: usize| daf.double(record[i * 8..i * 8 + 8].try_into().unwrap());
+            next = double(0) as u64;
+            // Saturates, so a wild count fails the check below.
+            let count = double(2) as usize;
+            let used = count
+                .checked_mul(summary_len)
+                .and_then(|n| n.checked_add(3));
+            if used.is_none_or(|used| used > RECORD_LEN / 8) {
+                return Err(err("too many summaries in a record"));
+            }
+            for s in 0..count {
+                let start = (3 + s * summary_len) * 8;
+                let doubles = (0..nd).map(|i| double(start / 8 + i)).collect();
+                let ints = (0..ni)
+                    .map(|i| {
+                        let at = start + nd * 8 + i * 4;
+                        daf.int(record[at..at + 4].try_into().unwrap())
+                    })
+                    .collect();
+                summaries.push(Summary { doubles, ints });
+            }
+        }
+        daf.summaries = summaries;
+        Ok(daf)
+    }
+
+    fn int(&self, bytes: [u8; 4]) -> i32 {
+        if self.little_endian {
+            i32::from_le_bytes(bytes)
+        } else {
+            i32::from_be_bytes(bytes)
+        }
+    }
+
+    fn double(&self, bytes: [u8; 8]) -> f64 {
+        if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        }
+    }
+
+    fn read_bytes(&self, offset: u64, len: usize) -> Result<Vec<u8>, SpiceError> {
+        let mut file = &self.file;
+        let mut bytes = vec![0; len];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// The doubles at addresses `start..=end`.
+    pub fn read_doubles(&self, start: usize, end: usize) -> Result<Vec<f64>, SpiceError> {
+        let in_file = (end as u64)
+            .checked_mul(8)
+            .is_some_and(|bytes| bytes <= self.len);
+        if start == 0 || end < start || !in_file {
+            return Err(SpiceError::Daf(format!(
+                "bad addresses {}..={}",
+                start, end
+            )));
+        }
+        let bytes = self.read_bytes((start as u64 - 1) * 8, (end - start + 1) * 8)?;
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|b| self.double(b.try_into().unwrap()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn testdata(name: &str) -> String {
+        format!("{}/testdata/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn reads_either_byte_order() {
+        for (name, little_endian) in [("ltl.bsp", true), ("big.bsp", false)] {
+            let daf = Daf::open(testdata(name)).unwrap();
+            assert_eq!(daf.little_endian, little_endian);
+            assert_eq!(daf.kind, "DAF/SPK");
+            assert_eq!(daf.summaries.len(), 2);
+            assert_eq!(daf.summaries[0].doubles, [-100.0, 100.0]);
+            assert_eq!(daf.summaries[0].ints, [399, 3, 17, 2, 385, 410]);
+            assert_eq!(daf.summaries[1].doubles, [-100.0, 100.0]);
+            assert_eq!(daf.summaries[1].ints, [3, 0, 1, 3, 411, 428]);
+            assert_eq!(daf.read_doubles(385, 387).unwrap(), [-50.0, 50.0, 1000.0]);
+        }
+    }
+
+    #[test]
+    fn follows_the_summary_records() {
+        // ND = 3 and NI = 3, so each summary is padded out to 5 doubles,
+        // and they're split over two records.
+        let daf = Daf::open(testdata("chained.daf")).unwrap();
+        assert_eq!(daf.kind, "DAF/TST");
+        let doubles: Vec<_> = daf.summaries.iter().map(|s| s.doubles.clone()).collect();
+        let ints: Vec<_> = daf.summaries.iter().map(|s| s.ints.clone()).collect();
+        assert_eq!(doubles, [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        assert_eq!(ints, [[-1, 2, -3], [4, -5, 6], [7, 8, 9]]);
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let message = |path: &str| match Daf::open(path) {
+            Err(SpiceError::Daf(message)) => message,
+            other => panic!("expected a DAF error, got {:?}", other),
+        };
+        assert_eq!(
+            message(concat!(env!("CARGO_MANIFEST_DIR"), "/bodies.csv")),
+            "not a DAF file"
+        );
+
+        // ltl.bsp with some bytes changed.
+        let broken = |at: usize, change: &[u8]| {
+            let mut bytes = fs::read(testdata("ltl.bsp")).unwrap();
+            bytes[at..at + change.len()].copy_from_slice(change);
+            let path = std::env::temp_dir().join(format!("gravsim-daf-{}.bsp", std::process::id()));
+            fs::write(&path, bytes).unwrap();
+            let broken = message(path.to_str().unwrap());
+            fs::remove_file(&path).unwrap();
+            broken
+        };
+
+        // A forward pointer past the end of the file.
+        assert_eq!(
+            broken(76, &9i32.to_le_bytes()),
+            "summary records run off the file"
+        );
+
+        // More summaries than fit in a record, however many that is. The
+        // summary record is record 2.
+        for count in [26.0, 1e6, 1e300, f64::INFINITY] {
+            assert_eq!(
+                broken(RECORD_LEN + 16, &f64::to_le_bytes(count)),
+                "too many summaries in a record",
+                "{}",
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn read_doubles_counts_from_one() {
+        let daf = Daf::open(testdata("ltl.bsp")).unwrap();
+        assert!(matches!(daf.read_doubles(0, 1), Err(SpiceError::Daf(_))));
+        assert!(matches!(daf.read_doubles(5, 4), Err(SpiceError::Daf(_))));
+    }
+
+    #[test]
+    fn read_doubles_stays_in_the_file() {
+        // 4 records of 128 doubles.
+        let daf = Daf::open(testdata("ltl.bsp")).unwrap();
+        assert_eq!(daf.read_doubles(512, 512).unwrap().len(), 1);
+        assert!(matches!(
+            daf.read_doubles(500, 513),
+            Err(SpiceError::Daf(_))
+        ));
+        assert!(matches!(
+            daf.read_doubles(1, usize::MAX),
+            Err(SpiceError::Daf(_))
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+// Text kernels: LSKs, PCKs, FKs and meta-kernels. Everything between a
+// `\begindata` and a `\begintext` line is assignments to the kernel pool,
+//
+//     BODY399_RADII     = ( 6378.1366   6378.1366   6356.7519 )
+//     DELTET/DELTA_AT   = ( 10, @1972-JAN-1
+//                           11, @1972-JUL-1 )
+//     KERNELS_TO_LOAD  += 'lsk/naif0012.tls'
+//
+// and everything else is comments.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::time::parse_calendar;
+use super::SpiceError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Numbers, and `@` dates as seconds past J2000.
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Assign,
+    Append,
+    Open,
+    Close,
+}
+
+/// The variables from every text kernel loaded so far. A later `=`
+/// replaces a variable, `+=` adds to it.
+#[derive(Debug, Default, Clone)]
+pub struct KernelPool {
+    vars: HashMap<String, Vec<Value>>,
+}
+
+impl KernelPool {
+    pub fn new() -> KernelPool {
+        KernelPool::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SpiceError> {
+        self.parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(&mut self, text: &str) -> Result<(), SpiceError> {
+        let mut tokens = vec![];
+        let mut data = false;
+        for (i, line) in text.lines().enumerate() {
+            match line.trim() {
+                "\\begindata" => data = true,
+                "\\begintext" => data = false,
+                _ if data => tokenize(line, i + 1, &mut tokens)?,
+                _ => {}
+            }
+        }
+
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some((line, token)) = tokens.next() {
+            let err = |message: String| SpiceError::Parse { line, message };
+            let Token::Word(name) = token else {
+                return Err(err("expected a variable name".to_string()));
+            };
+            let append = match tokens.next() {
+                Some((_, Token::Assign)) => false,
+                Some((_, Token::Append)) => true,
+                _ => return Err(err(format!("expected `=` or `+=` after {}", name))),
+            };
+
+            let mut values = vec![];
+            match tokens.next() {
+                Some((_, Token::Open)) => loop {
+                    match tokens.next() {
+                        Some((_, Token::Close)) => break,
+                        Some((line, token)) => values.push(value(token, line)?),
+                        None => return Err(err(format!("{} is missing its `)`", name))),
+                    }
+                },
+                Some((line, token)) => values.push(value(token, line)?),
+                None => return Err(err(format!("{} has no value", name))),
+            }
+
+            if append {
+                self.vars.entry(name).or_default().extend(values);
+            } else {
+                self.vars.insert(name, values);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[Value]> {
+        self.vars.get(name).map(|v| v.as_slice())
+    }
+
+    /// The variable's values, if they're all numbers.
+    pub fn numbers(&self, name: &str) -> Option<Vec<f64>> {
+        self.get(name)?
+            .iter()
+            .map(|v| match v {
+                Value::Number(n) => Some(*n),
+                Value::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// The variable's first value, if it's a number.
+    pub fn number(&self, name: &str) -> Option<f64> {
+        match self.get(name)?.first()? {
+            Value::Number(n) => Some(*n),
+            Value::Text(_) => None,
+        }
+    }
+
+    /// The variable's values, if they're all strings.
+    pub fn strings(&self, name: &str) -> Option<Vec<&str>> {
+        self.get(name)?
+            .iter()
+            .map(|v| match v {
+                Value::Text(s) => Some(s.as_str()),
+                Value::Number(_) => None,
+            })
+            .collect()
+    }
+}
+
+fn value(token: Token, line: usize) -> Result<Value, SpiceError> {
+    let err = |message: String| SpiceError::Parse { line, message };
+    match token {
+        Token::Text(s) => Ok(Value::Text(s)),
+        Token::Word(w) => {
+            if let Some(date) = w.strip_prefix('@') {
+                parse_calendar(date)
+                    .map(Value::Number)
+                    .ok_or_else(|| err(format!("`{}` isn't a date", w)))
+            } else {
+                // Fortran writes exponents with a D.
+                w.replace(['D', 'd'], "E")
+                    .parse()
+                    .map(Value::Number)
+                    .map_err(|_| err(format!("`{}` isn't a number", w)))
+            }
+        }
+        _ => Err(err("expected a value".to_string())),
+    }
+}
+
+fn tokenize(line: &str, n: usize, tokens: &mut Vec<(usize, Token)>) -> Result<(), SpiceError> {
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() || c == ',' => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' => Token::Assign,
+            '+' if chars.peek() == Some(&'=') => {
+                chars.next();
+                Token::Append
+            }
+            '\'' => {
+                // A quote inside a string is written twice.
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            s.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(c) => s.push(c),
+                        None => {
+                            return Err(SpiceError::Parse {
+                                line: n,
+                                message: "unterminated string".to_string(),
+                            })
+                        }
+                    }
+                }
+                Token::Text(s)
+            }
+            c => {
+                let mut w = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "(),='".contains(c) {
+                        break;
+                    }
+                    w.push(c);
+                    chars.next();
+                }
+                Token::Word(w)
+            }
+        };
+        tokens.push((n, token));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_data_blocks_are_read() {
+        let mut pool = KernelPool::new();
+        pool.parse(
+            "KPL/PCK\n\
+             A = 1\n\
+             \\begindata\n\
+             B = ( 1, 2.5D3 -4d-1 )\n\
+             NAME = 'it''s'\n\
+             \\begintext\n\
+             C = 3\n",
+        )
+        .unwrap();
+        assert_eq!(pool.get("A"), None);
+        assert_eq!(pool.get("C"), None);
+        assert_eq!(pool.numbers("B"), Some(vec![1.0, 2500.0, -0.4]));
+        assert_eq!(pool.strings("NAME"), Some(vec!["it's"]));
+        assert_eq!(pool.numbers("NAME"), None);
+    }
+
+    #[test]
+    fn assign_replaces_and_append_adds() {
+        let mut pool = KernelPool::new();
+        pool.parse("\\begindata\nX = ( 1 2 )\nY = 'a'\nY += ( 'b'\n'c' )\n")
+            .unwrap();
+        pool.parse("\\begindata\nX = 3\n").unwrap();
+        assert_eq!(pool.numbers("X"), Some(vec![3.0]));
+        assert_eq!(pool.strings("Y"), Some(vec!["a", "b", "c"]));
+    }
+
+    #[test]
+    fn dates_are_seconds_past_j2000() {
+        let mut pool = KernelPool::new();
+        pool.parse("\\begindata\nT = ( @2000-JAN-2 @1999-12-31 )\n")
+            .unwrap();
+        assert_eq!(pool.numbers("T"), Some(vec![43200.0, -129600.0]));
+    }
+
+    #[test]
+    fn errors_give_the_line() {
+        let line = |text: &str| match KernelPool::new().parse(text) {
+            Err(SpiceError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(line("\\begindata\n\nQ = ( 1 zz )\n"), 3);
+        assert_eq!(line("\\begindata\nQ = ( 1\n"), 2);
+        assert_eq!(line("\\begindata\nQ 1\n"), 2);
+        assert_eq!(line("\\begindata\nQ = 'open\n"), 2);
+        assert_eq!(line("\\begindata\nQ = @sometime\n"), 2);
+    }
+}
@@ -0,0 +1,313 @@
+// Reads NAIF SPICE kernels straight from disk, so initial conditions can
+// come from the ephemerides at any epoch instead of from states dumped by
+// hand with `spacit` and `brief`.
+//
+//     let mut ephemeris = Ephemeris::new();
+//     ephemeris.load("spice/solar_system_v0063.tm")?;
+//     let et = ephemeris.utc_to_et("2018-04-06 00:00:00")?;
+//     let (sim, ids) = ephemeris.simulation(&[10, 199, 299, 399, 301], et)?;
+//
+// Bodies are named by their NAIF codes. States come out relative to the
+// solar system barycenter in the ECLIPJ2000 frame, in m and m/s, like
+// bodies.csv.
+
+pub mod daf;
+pub mod kernel;
+pub mod spk;
+pub mod time;
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::{BodyId, NBodySimulation, SimObj, Vec3};
+use daf::Daf;
+use kernel::KernelPool;
+use spk::Spk;
+use time::{parse_calendar, Lsk};
+
+/// The solar system barycenter.
+pub const SSB: i32 = 0;
+
+const J2000_FRAME: i32 = 1;
+const ECLIPJ2000_FRAME: i32 = 17;
+/// The obliquity of the ecliptic at J2000 (IAU 1976), in radians.
+const OBLIQUITY: f64 = 84381.448 / 3600.0 * std::f64::consts::PI / 180.0;
+
+#[derive(Debug)]
+pub enum SpiceError {
+    Io(io::Error),
+    /// In a text kernel; `line` is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// A binary kernel that doesn't hold together.
+    Daf(String),
+    /// Which kernel an error came from.
+    Kernel(PathBuf, Box<SpiceError>),
+    /// A kernel pool variable that hasn't been loaded.
+    Missing(String),
+    Unsupported(String),
+    /// No loaded segment has `target` at `et`.
+    NoCoverage {
+        target: i32,
+        et: f64,
+    },
+    /// A UTC time before the first leap second in the LSK.
+    BeforeLeapSeconds(f64),
+    BadTime(String),
+}
+
+impl From<io::Error> for SpiceError {
+    fn from(e: io::Error) -> Self {
+        SpiceError::Io(e)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Ephemeris {
+    pool: KernelPool,
+    spks: Vec<Spk>,
+}
+
+impl Ephemeris {
+    pub fn new() -> Ephemeris {
+        Ephemeris::default()
+    }
+
+    /// Loads a kernel by what's in it, not its name: SPKs are read for
+    /// states, text kernels go into the pool, and a meta-kernel loads its
+    /// `KERNELS_TO_LOAD`. Other binary kernels, like the PCKs of Earth's
+    /// and the Moon's orientation, aren't used and are skipped.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SpiceError> {
+        let path = path.as_ref();
+        let in_kernel = |e| SpiceError::Kernel(path.to_path_buf(), Box::new(e));
+
+        let mut magic = [0u8; 8];
+        let read = File::open(path)
+            .and_then(|mut f| f.read(&mut magic))
+            .map_err(|e| in_kernel(e.into()))?;
+        let magic = &magic[..read];
+
+        if magic.starts_with(b"DAF/") {
+            let daf = Daf::open(path).map_err(in_kernel)?;
+            if daf.kind == "DAF/SPK" {
+                self.spks.push(Spk::from_daf(daf).map_err(in_kernel)?);
+            }
+            Ok(())
+        } else if magic.starts_with(b"KPL/MK") {
+            let mut meta = KernelPool::new();
+            meta.load(path).map_err(in_kernel)?;
+            for kernel in meta_kernels(&meta).map_err(in_kernel)? {
+                self.load(kernel)?;
+            }
+            Ok(())
+        } else {
+            self.pool.load(path).map_err(in_kernel)
+        }
+    }
+
+    /// The variables from the text kernels.
+    pub fn pool(&self) -> &KernelPool {
+        &self.pool
+    }
+
+    /// ET for a UTC time like `2018-04-06 00:00:00`, with the loaded LSK.
+    pub fn utc_to_et(&self, utc: &str) -> Result<f64, SpiceError> {
+        let utc = parse_calendar(utc).ok_or_else(|| SpiceError::BadTime(utc.to_string()))?;
+        Lsk::from_pool(&self.pool)?.utc_to_et(utc)
+    }
+
+    /// The bodies some segment has at `et`.
+    pub fn targets(&self, et: f64) -> Vec<i32> {
+        let mut targets: Vec<i32> = self
+            .spks
+            .iter()
+            .flat_map(|spk| &spk.segments)
+            .filter(|s| s.covers(et))
+            .map(|s| s.target)
+            .collect();
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+
+    /// A body's position and velocity relative to the solar system
+    /// barycenter, in m and m/s in ECLIPJ2000.
+    pub fn state(&self, target: i32, et: f64) -> Result<(Vec3, Vec3), SpiceError> {
+        let mut position = Vec3::new(0.0, 0.0, 0.0);
+        let mut velocity = Vec3::new(0.0, 0.0, 0.0);
+
+        // Segments are relative to some center, which has its own segment,
+        // and so on down to the barycenter. Like SPICE, the last loaded
+        // segment covering the time wins.
+        let mut body = target;
+        let mut hops = 0;
+        while body != SSB {
+            let (spk, segment) = self
+                .spks
+                .iter()
+                .rev()
+                .find_map(|spk| {
+                    spk.segments
+                        .iter()
+                        .rev()
+                        .find(|s| s.target == body && s.covers(et))
+                        .map(|s| (spk, s))
+                })
+                .ok_or(SpiceError::NoCoverage { target: body, et })?;
+            hops += 1;
+            if hops > 100 {
+                return Err(SpiceError::Daf(format!(
+                    "segments for {} go in a loop",
+                    target
+                )));
+            }
+
+            let s = spk.state(segment, et)?;
+            let (p, v) = to_eclipj2000(segment.frame, s)?;
+            position += &p;
+            velocity += &v;
+            body = segment.center;
+        }
+        Ok((position, velocity))
+    }
+
+    /// A body's mass from its `BODYnnn_GM`, for a simulation with
+    /// gravitational constant `g`.
+    pub fn mass(&self, body: i32, g: f64) -> Result<f64, SpiceError> {
+        let name = format!("BODY{}_GM", body);
+        match self.pool.number(&name) {
+            // km^3/s^2
+            Some(gm) if gm > 0.0 => Ok(gm * 1e9 / g),
+            _ => Err(SpiceError::Missing(name)),
+        }
+    }
+
+    /// A simulation of `bodies` at `et`, and their ids in the same order.
+    /// Every body needs a state from an SPK and a GM from a PCK.
+    pub fn simulation(
+        &self,
+        bodies: &[i32],
+        et: f64,
+    ) -> Result<(NBodySimulation, Vec<BodyId>), SpiceError> {
+        let mut sim = NBodySimulation::new();
+        let g = sim.gravitational_constant();
+        let mut ids = vec![];
+        for &body in bodies {
+            let (position, velocity) = self.state(body, et)?;
+            ids.push(sim.add_body(SimObj {
+                position,
+                velocity,
+                mass: self.mass(body, g)?,
+                ..SimObj::default()
+            }));
+        }
+        Ok((sim, ids))
+    }
+}
+
+/// A meta-kernel's `KERNELS_TO_LOAD`, with `$SYMBOL`s swapped for their
+/// `PATH_VALUES`. Relative paths are from the working directory, as in
+/// SPICE.
+fn meta_kernels(meta: &KernelPool) -> Result<Vec<String>, SpiceError> {
+    let symbols = meta.strings("PATH_SYMBOLS").unwrap_or_default();
+    let values = meta.strings("PATH_VALUES").unwrap_or_default();
+    if symbols.len() != values.len() {
+        return Err(SpiceError::Missing(
+            "a PATH_VALUES for each PATH_SYMBOLS".to_string(),
+        ));
+    }
+    let kernels = meta
+        .strings("KERNELS_TO_LOAD")
+        .ok_or_else(|| SpiceError::Missing("KERNELS_TO_LOAD".to_string()))?;
+
+    Ok(kernels
+        .iter()
+        .map(|k| {
+            symbols
+                .iter()
+                .zip(&values)
+                .fold(k.to_string(), |k, (s, v)| k.replace(&format!("${}", s), v))
+        })
+        .collect())
+}
+
+fn to_eclipj2000(frame: i32, s: [f64; 6]) -> Result<(Vec3, Vec3), SpiceError> {
+    // km to m
+    let p = [s[0] * 1e3, s[1] * 1e3, s[2] * 1e3];
+    let v = [s[3] * 1e3, s[4] * 1e3, s[5] * 1e3];
+    match frame {
+        ECLIPJ2000_FRAME => Ok((Vec3::new(p[0], p[1], p[2]), Vec3::new(v[0], v[1], v[2]))),
+        J2000_FRAME => {
+            // Tilt the equator down onto the ecliptic about the x axis.
+            let (sin, cos) = OBLIQUITY.sin_cos();
+            let rotate =
+                |a: [f64; 3]| Vec3::new(a[0], cos * a[1] + sin * a[2], -sin * a[1] + cos * a[2]);
+            Ok((rotate(p), rotate(v)))
+        }
+        f => Err(SpiceError::Unsupported(format!("frame {}", f))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ephemeris() -> Ephemeris {
+        let mut ephemeris = Ephemeris::new();
+        for name in ["ltl.bsp", "leapseconds.tls"] {
+            ephemeris
+                .load(format!("{}/testdata/{}", env!("CARGO_MANIFEST_DIR"), name))
+                .unwrap();
+        }
+        ephemeris
+    }
+
+    #[test]
+    fn states_chain_down_to_the_barycenter() {
+        let ephemeris = ephemeris();
+        assert_eq!(ephemeris.targets(0.0), [3, 399]);
+        assert!(ephemeris.targets(200.0).is_empty());
+
+        // The Earth about its barycenter is ECLIPJ2000 already; the
+        // barycenter is J2000, so it's turned about x onto the ecliptic.
+        // See spk.rs for the raw states.
+        let (sin, cos) = OBLIQUITY.sin_cos();
+        let (p, v) = ephemeris.state(399, 50.0).unwrap();
+        let (by, bz) = (2e7 - 2.5, 0.5);
+        let expected_p = [
+            1090.0 + 1.5e8 + 5.0,
+            1980.0 + cos * by + sin * bz,
+            3030.0 - sin * by + cos * bz,
+        ];
+        let expected_v = [2.0 - 1.75, -4.0 + cos * 30.0, 6.0 - sin * 30.0];
+        for (got, want) in [p.x, p.y, p.z].iter().zip(expected_p) {
+            assert!((got - want * 1e3).abs() < 1e-3, "{} != {}", got, want * 1e3);
+        }
+        for (got, want) in [v.x, v.y, v.z].iter().zip(expected_v) {
+            assert!((got - want * 1e3).abs() < 1e-9, "{} != {}", got, want * 1e3);
+        }
+
+        assert!(matches!(
+            ephemeris.state(399, 500.0),
+            Err(SpiceError::NoCoverage { target: 399, .. })
+        ));
+    }
+
+    #[test]
+    fn utc_to_et_uses_the_loaded_lsk() {
+        let ephemeris = ephemeris();
+        let et = ephemeris.utc_to_et("2018-04-06 00:00:00").unwrap();
+        assert_eq!(ephemeris.utc_to_et("2018-APR-06T00:00:00").unwrap(), et);
+        assert!(matches!(
+            ephemeris.utc_to_et("soon"),
+            Err(SpiceError::BadTime(_))
+        ));
+        assert!(matches!(
+            Ephemeris::new().utc_to_et("2018-04-06"),
+            Err(SpiceError::Missing(_))
+        ));
+    }
+}
@@ -0,0 +1,239 @@
+// SPK ephemerides. Each segment gives one body's position relative to
+// another over a span of time, in km and km/s. Only the Chebyshev types
+// are read: type 2 fits the position and differentiates it for the
+// velocity, type 3 fits both.
+
+use std::path::Path;
+
+use super::daf::Daf;
+use super::SpiceError;
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub target: i32,
+    pub center: i32,
+    pub frame: i32,
+    pub data_type: i32,
+    /// ET span it covers.
+    pub start: f64,
+    pub end: f64,
+    /// Addresses of its doubles in the file.
+    begin: usize,
+    last: usize,
+}
+
+impl Segment {
+    pub fn covers(&self, et: f64) -> bool {
+        self.start <= et && et <= self.end
+    }
+}
+
+#[derive(Debug)]
+pub struct Spk {
+    daf: Daf,
+    pub segments: Vec<Segment>,
+}
+
+impl Spk {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Spk, SpiceError> {
+        Spk::from_daf(Daf::open(path)?)
+    }
+
+    pub fn from_daf(daf: Daf) -> Result<Spk, SpiceError> {
+        if daf.kind != "DAF/SPK" {
+            return Err(SpiceError::Daf(format!("{} isn't an SPK", daf.kind)));
+        }
+        let segments = daf
+            .summaries
+            .iter()
+            .map(|s| {
+                if s.doubles.len() < 2 || s.ints.len() < 6 {
+                    return Err(SpiceError::Daf("short SPK summary".to_string()));
+                }
+                let address = |i: i32| {
+                    usize::try_from(i)
+                        .map_err(|_| SpiceError::Daf(format!("bad SPK address {}", i)))
+                };
+                Ok(Segment {
+                    target: s.ints[0],
+                    center: s.ints[1],
+                    frame: s.ints[2],
+                    data_type: s.ints[3],
+                    start: s.doubles[0],
+                    end: s.doubles[1],
+                    begin: address(s.ints[4])?,
+                    last: address(s.ints[5])?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Spk { daf, segments })
+    }
+
+    /// The segment's position and velocity at `et`, relative to its center
+    /// in its frame.
+    pub fn state(&self, segment: &Segment, et: f64) -> Result<[f64; 6], SpiceError> {
+        let components = match segment.data_type {
+            2 => 3,
+            3 => 6,
+            t => return Err(SpiceError::Unsupported(format!("SPK type {}", t))),
+        };
+        let err =
+            |message: &str| SpiceError::Daf(format!("segment for {}: {}", segment.target, message));
+
+        // The directory at the end: the first record's start, how long each
+        // covers, how long each is and how many there are.
+        if segment.begin == 0 || segment.last < segment.begin.saturating_add(3) {
+            return Err(err("too short"));
+        }
+        let directory = self.daf.read_doubles(segment.last - 3, segment.last)?;
+        let (init, length, size, n) = (
+            directory[0],
+            directory[1],
+            directory[2] as usize,
+            directory[3] as usize,
+        );
+        if n == 0 || length <= 0.0 || size < 2 + components || (size - 2) % components != 0 {
+            return Err(err("bad directory"));
+        }
+        // The records have to end before the directory starts.
+        let records_end = n
+            .checked_mul(size)
+            .and_then(|len| len.checked_add(segment.begin));
+        if records_end.is_none_or(|end| end > segment.last - 3) {
+            return Err(err("records run into the directory"));
+        }
+
+        let index = (((et - init) / length).floor().max(0.0) as usize).min(n - 1);
+        let start = segment.begin + index * size;
+        let record = self.daf.read_doubles(start, start + size - 1)?;
+        let (mid, radius) = (record[0], record[1]);
+        let degree = (size - 2) / components;
+        let s = (et - mid) / radius;
+
+        // T_n(s) and their derivatives, by the recurrences.
+        let mut t = vec![0.0; degree];
+        let mut dt = vec![0.0; degree];
+        t[0] = 1.0;
+        if degree > 1 {
+            t[1] = s;
+            dt[1] = 1.0;
+        }
+        for i in 2..degree {
+            t[i] = 2.0 * s * t[i - 1] - t[i - 2];
+            dt[i] = 2.0 * t[i - 1] + 2.0 * s * dt[i - 1] - dt[i - 2];
+        }
+
+        let mut state = [0.0; 6];
+        for c in 0..3 {
+            let coeffs = &record[2 + c * degree..2 + (c + 1) * degree];
+            state[c] = coeffs.iter().zip(&t).map(|(a, b)| a * b).sum();
+            if components == 3 {
+                state[c + 3] = coeffs.iter().zip(&dt).map(|(a, b)| a * b).sum::<f64>() / radius;
+            } else {
+                let coeffs = &record[2 + (c + 3) * degree..2 + (c + 4) * degree];
+                state[c + 3] = coeffs.iter().zip(&t).map(|(a, b)| a * b).sum();
+            }
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testdata(name: &str) -> String {
+        format!("{}/testdata/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn assert_close(a: [f64; 6], b: [f64; 6]) {
+        for (x, y) in a.iter().zip(&b) {
+            assert!(
+                (x - y).abs() <= 1e-9 * y.abs().max(1.0),
+                "{:?} != {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn segments_come_from_the_summaries() {
+        let spk = Spk::open(testdata("ltl.bsp")).unwrap();
+        let s = &spk.segments[0];
+        assert_eq!((s.target, s.center, s.frame, s.data_type), (399, 3, 17, 2));
+        assert!(s.covers(-100.0) && s.covers(100.0) && !s.covers(100.5));
+        let s = &spk.segments[1];
+        assert_eq!((s.target, s.center, s.frame, s.data_type), (3, 0, 1, 3));
+    }
+
+    #[test]
+    fn type_2_differentiates_the_position() {
+        // See make_kernels.py. At -50, the middle of the first record,
+        // T = (1, 0, -1) and T' = (0, 1, 0) over a radius of 50 s. At 25,
+        // in the second, s = -0.5: T = (1, -0.5, -0.5) and T' = (0, 1, -2).
+        for name in ["ltl.bsp", "big.bsp"] {
+            let spk = Spk::open(testdata(name)).unwrap();
+            let earth = &spk.segments[0];
+            assert_close(
+                spk.state(earth, -50.0).unwrap(),
+                [990.0, 1980.0, 3030.0, 2.0, -4.0, 6.0],
+            );
+            assert_close(
+                spk.state(earth, 25.0).unwrap(),
+                [1045.0, 2090.0, 2865.0, 1.6, -4.8, 7.2],
+            );
+        }
+    }
+
+    #[test]
+    fn type_3_fits_the_velocity_too() {
+        // One record of radius 100 s about 0, so s = 0.5 at 50.
+        for name in ["ltl.bsp", "big.bsp"] {
+            let spk = Spk::open(testdata(name)).unwrap();
+            let barycenter = &spk.segments[1];
+            assert_close(
+                spk.state(barycenter, 50.0).unwrap(),
+                [1.5e8 + 5.0, 2e7 - 2.5, 0.5, -1.75, 30.0, 0.0],
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_what_isnt_an_spk() {
+        let daf = Daf::open(testdata("chained.daf")).unwrap();
+        assert!(matches!(Spk::from_daf(daf), Err(SpiceError::Daf(_))));
+    }
+
+    #[test]
+    fn negative_addresses_are_rejected() {
+        let mut bytes = std::fs::read(testdata("ltl.bsp")).unwrap();
+        // The first summary's begin address, in the summary record.
+        bytes[1080..1084].copy_from_slice(&(-1i32).to_le_bytes());
+        let path = std::env::temp_dir().join(format!("gravsim-spk-{}.bsp", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let spk = Spk::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(spk, Err(SpiceError::Daf(m)) if m == "bad SPK address -1"));
+    }
+
+    #[test]
+    fn records_have_to_fit_before_the_directory() {
+        let spk = Spk::open(testdata("ltl.bsp")).unwrap();
+        // The earth's directory, 2 records of 11 doubles, over the start
+        // of the barycenter's data: they'd run past their directory.
+        let overlapping = Segment {
+            begin: 400,
+            ..spk.segments[0].clone()
+        };
+        assert!(matches!(
+            spk.state(&overlapping, 0.0),
+            Err(SpiceError::Daf(m)) if m.ends_with("records run into the directory")
+        ));
+        let empty = Segment {
+            begin: 0,
+            ..spk.segments[0].clone()
+        };
+        assert!(matches!(spk.state(&empty, 0.0), Err(SpiceError::Daf(_))));
+    }
+}
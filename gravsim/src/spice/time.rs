@@ -0,0 +1,230 @@
+// Times. SPK data is indexed by ephemeris time (ET): TDB seconds past
+// J2000, 2000-01-01 12:00:00 TDB. The LSK holds the leap seconds and the
+// constants to get there from UTC.
+
+use super::kernel::KernelPool;
+use super::SpiceError;
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// Days from 1970-01-01 to a date in the proleptic Gregorian calendar.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn days_in_month(y: i64, m: i64) -> i64 {
+    match m {
+        2 if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Reads `2018-04-06`, `2018-APR-06`, `1972-JAN-1` and the like, with an
+/// optional `HH:MM:SS.sss` after a space or a `T`, as calendar seconds
+/// past J2000. There are no leap seconds in this count.
+pub fn parse_calendar(s: &str) -> Option<f64> {
+    let s = s.trim();
+    // The time starts after the day, so the T in OCT isn't taken for it.
+    let day_at = s.match_indices('-').nth(1)?.0 + 1;
+    let (date, time) = match s[day_at..].find([' ', 'T']) {
+        Some(i) => (&s[..day_at + i], s[day_at + i + 1..].trim()),
+        None => (s, ""),
+    };
+
+    let mut date = date.split('-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month = date.next()?;
+    let month = match month.parse::<i64>() {
+        Ok(m) => m,
+        Err(_) => MONTHS.iter().position(|&m| m.eq_ignore_ascii_case(month))? as i64 + 1,
+    };
+    let day: i64 = date.next()?.parse().ok()?;
+    if date.next().is_some()
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+    {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    if !time.is_empty() {
+        let mut scale = 3600.0;
+        for part in time.split(':') {
+            if scale < 1.0 {
+                return None;
+            }
+            seconds += scale * part.parse::<f64>().ok()?;
+            scale /= 60.0;
+        }
+    }
+
+    let days = days_from_civil(year, month, day) - days_from_civil(2000, 1, 1);
+    Some(days as f64 * 86400.0 + seconds - 43200.0)
+}
+
+/// A leapseconds kernel's `DELTET/` variables.
+#[derive(Debug, Clone)]
+pub struct Lsk {
+    /// TT - TAI
+    delta_t_a: f64,
+    k: f64,
+    eb: f64,
+    m: [f64; 2],
+    /// (TAI - UTC, from when) pairs, oldest first.
+    delta_at: Vec<(f64, f64)>,
+}
+
+impl Lsk {
+    pub fn from_pool(pool: &KernelPool) -> Result<Lsk, SpiceError> {
+        let number = |name: &str| {
+            pool.number(name)
+                .ok_or_else(|| SpiceError::Missing(name.to_string()))
+        };
+        let m = pool
+            .numbers("DELTET/M")
+            .filter(|m| m.len() == 2)
+            .ok_or_else(|| SpiceError::Missing("DELTET/M".to_string()))?;
+        let delta_at = pool
+            .numbers("DELTET/DELTA_AT")
+            .filter(|d| !d.is_empty() && d.len() % 2 == 0)
+            .ok_or_else(|| SpiceError::Missing("DELTET/DELTA_AT".to_string()))?;
+
+        Ok(Lsk {
+            delta_t_a: number("DELTET/DELTA_T_A")?,
+            k: number("DELTET/K")?,
+            eb: number("DELTET/EB")?,
+            m: [m[0], m[1]],
+            delta_at: delta_at.chunks(2).map(|p| (p[0], p[1])).collect(),
+        })
+    }
+
+    /// ET for a UTC time in calendar seconds past J2000.
+    pub fn utc_to_et(&self, utc: f64) -> Result<f64, SpiceError> {
+        let (delta_at, _) = self
+            .delta_at
+            .iter()
+            .rev()
+            .find(|&&(_, from)| from <= utc)
+            .ok_or(SpiceError::BeforeLeapSeconds(utc))?;
+        let tt = utc + delta_at + self.delta_t_a;
+
+        // TDB - TT is periodic, from Earth's eccentric orbit.
+        let m = self.m[0] + self.m[1] * tt;
+        let e = m + self.eb * m.sin();
+        Ok(tt + self.k * e.sin())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The leap seconds from 2006 on; see the file.
+    fn lsk() -> Lsk {
+        let mut pool = KernelPool::new();
+        pool.load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/leapseconds.tls"
+        ))
+        .unwrap();
+        Lsk::from_pool(&pool).unwrap()
+    }
+
+    #[test]
+    fn calendar_dates() {
+        assert_eq!(parse_calendar("2000-01-01 12:00:00"), Some(0.0));
+        assert_eq!(parse_calendar("2000-01-02"), Some(43200.0));
+        assert_eq!(parse_calendar("1999-12-31T00:00:00.5"), Some(-129599.5));
+        assert_eq!(
+            parse_calendar("2018-APR-06"),
+            parse_calendar("2018-04-06T00:00:00")
+        );
+        assert_eq!(parse_calendar("1972-jan-1"), parse_calendar("1972-01-01"));
+        assert_eq!(parse_calendar("2018-OCT-06"), parse_calendar("2018-10-06"));
+        assert_eq!(
+            parse_calendar("2018-oct-06T12:00:00"),
+            parse_calendar("2018-10-06 12:00:00")
+        );
+        assert_eq!(
+            parse_calendar("2018-OCT-06 06:30"),
+            parse_calendar("2018-10-06T06:30:00")
+        );
+        for bad in [
+            "2018-13-01",
+            "2018-OCT",
+            "2018-OCTOBER-06",
+            "2018-04",
+            "2018-04-06 12:00:00:00:00",
+            "yesterday",
+        ] {
+            assert_eq!(parse_calendar(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn days_past_the_end_of_the_month_are_rejected() {
+        for bad in [
+            "2018-02-31",
+            "2018-02-29",
+            "1900-02-29",
+            "2018-04-31",
+            "2018-DEC-32",
+        ] {
+            assert_eq!(parse_calendar(bad), None, "{}", bad);
+        }
+        for good in ["2016-02-29", "2000-02-29", "2018-04-30", "2018-DEC-31"] {
+            assert!(parse_calendar(good).is_some(), "{}", good);
+        }
+        assert_eq!(
+            parse_calendar("2018-03-01").unwrap() - parse_calendar("2018-02-28").unwrap(),
+            86400.0
+        );
+    }
+
+    #[test]
+    fn utc_to_et() {
+        // 37 leap seconds and TT - TAI of 32.184 s, and in April TDB is
+        // about 1.66 ms ahead of TT.
+        let utc = parse_calendar("2018-04-06 00:00:00").unwrap();
+        assert_eq!(utc, 576244800.0);
+        let et = lsk().utc_to_et(utc).unwrap();
+        assert!((et - 576244869.18566).abs() < 1e-5, "{}", et);
+    }
+
+    #[test]
+    fn utc_to_et_counts_leap_seconds() {
+        // The leap second at the end of 2016 makes the UTC second before
+        // 2017 two seconds long.
+        let lsk = lsk();
+        let before = lsk
+            .utc_to_et(parse_calendar("2016-12-31 23:59:59").unwrap())
+            .unwrap();
+        let after = lsk
+            .utc_to_et(parse_calendar("2017-01-01 00:00:00").unwrap())
+            .unwrap();
+        assert!((after - before - 2.0).abs() < 1e-6, "{}", after - before);
+
+        let utc = parse_calendar("1971-12-31").unwrap();
+        assert!(matches!(
+            lsk.utc_to_et(utc),
+            Err(SpiceError::BeforeLeapSeconds(t)) if t == utc
+        ));
+    }
+
+    #[test]
+    fn lsk_needs_every_variable() {
+        let mut pool = KernelPool::new();
+        pool.parse("\\begindata\nDELTET/DELTA_T_A = 32.184\n")
+            .unwrap();
+        assert!(matches!(Lsk::from_pool(&pool), Err(SpiceError::Missing(_))));
+    }
+}
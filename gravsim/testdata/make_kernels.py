@@ -0,0 +1,99 @@
+#!/usr/bin/env python3
+# Writes the binary kernel fragments the spice tests read:
+#
+#   ltl.bsp, big.bsp  the same two SPK segments, little and big endian
+#   chained.daf       a DAF with 3 doubles and 3 integers per summary and
+#                     its summaries over two chained records
+#
+# The Chebyshev coefficients are made up, so the tests can work the states
+# out by hand. Run it from this directory.
+
+import struct
+
+RECORD = 1024
+DOUBLES = RECORD // 8
+
+
+def file_record(order, kind, nd, ni, fward, bward, free):
+    fmt = "LTL-IEEE" if order == "<" else "BIG-IEEE"
+    r = kind.ljust(8).encode()
+    r += struct.pack(order + "ii", nd, ni)
+    r += b"test fragment".ljust(60)
+    r += struct.pack(order + "iii", fward, bward, free)
+    r += fmt.encode()
+    return r.ljust(RECORD, b"\0")
+
+
+def summary_record(order, nd, ni, next_, prev, summaries):
+    r = struct.pack(order + "ddd", next_, prev, len(summaries))
+    for doubles, ints in summaries:
+        s = struct.pack(order + "d" * nd, *doubles)
+        s += struct.pack(order + "i" * ni, *ints)
+        r += s.ljust((nd + (ni + 1) // 2) * 8, b"\0")
+    return r.ljust(RECORD, b"\0")
+
+
+def name_record(names):
+    return b"".join(n.ljust(40).encode() for n in names).ljust(RECORD, b" ")
+
+
+def chebyshev_segment(init, length, records):
+    """Type 2 or 3 data: each record is mid, radius and its coefficients,
+    then the directory."""
+    data = []
+    for r in records:
+        data += r
+    return data + [init, length, len(records[0]), len(records)]
+
+
+# Type 2: the Earth about the Earth-Moon barycenter from ET -100 to 100, in
+# two records of degree 2 (three coefficients) for x, y and z.
+TYPE2 = chebyshev_segment(-100.0, 100.0, [
+    [-50.0, 50.0, 1000.0, 100.0, 10.0, 2000.0, -200.0, 20.0, 3000.0, 300.0, -30.0],
+    [50.0, 50.0, 1100.0, 100.0, 10.0, 2000.0, -200.0, 20.0, 3000.0, 300.0, -30.0],
+])
+# Type 3: the Earth-Moon barycenter about the solar system barycenter over
+# the same span, in one record of degree 1 for x, y, z, vx, vy and vz.
+TYPE3 = chebyshev_segment(-100.0, 200.0, [
+    [0.0, 100.0, 1.5e8, 10.0, 2e7, -5.0, 0.0, 1.0, -2.0, 0.5, 30.0, 0.0, 0.0, 0.0],
+])
+
+
+def spk(order):
+    # Records: file, summaries, names, then the data from address 385.
+    begin2 = 3 * DOUBLES + 1
+    end2 = begin2 + len(TYPE2) - 1
+    begin3 = end2 + 1
+    end3 = begin3 + len(TYPE3) - 1
+    data = struct.pack(order + "d" * (len(TYPE2) + len(TYPE3)), *TYPE2, *TYPE3)
+    return (
+        file_record(order, "DAF/SPK", 2, 6, 2, 2, end3 + 1)
+        + summary_record(order, 2, 6, 0, 0, [
+            ([-100.0, 100.0], [399, 3, 17, 2, begin2, end2]),
+            ([-100.0, 100.0], [3, 0, 1, 3, begin3, end3]),
+        ])
+        + name_record(["EARTH", "EARTH BARYCENTER"])
+        + data.ljust(RECORD, b"\0")
+    )
+
+
+def chained():
+    # Summary records 2 and 4, with their names in 3 and 5.
+    order = ">"
+    return (
+        file_record(order, "DAF/TST", 3, 3, 2, 4, 5 * DOUBLES + 1)
+        + summary_record(order, 3, 3, 4, 0, [
+            ([1.0, 2.0, 3.0], [-1, 2, -3]),
+            ([4.0, 5.0, 6.0], [4, -5, 6]),
+        ])
+        + name_record(["ONE", "TWO"])
+        + summary_record(order, 3, 3, 0, 2, [
+            ([7.0, 8.0, 9.0], [7, 8, 9]),
+        ])
+        + name_record(["THREE"])
+    )
+
+
+for name, contents in [("ltl.bsp", spk("<")), ("big.bsp", spk(">")), ("chained.daf", chained())]:
+    with open(name, "wb") as f:
+        f.write(contents)
@@ -0,0 +1,62 @@
+// gravsim as another crate sees it: everything here goes through the
+// public API.
+
+use gravsim::{BodyKind, Catalog, Integrator, NBodySimulation, SimObj, Vec3};
+
+#[test]
+fn a_ship_orbits_a_planet() {
+    let mut sim = NBodySimulation::new();
+    let mu = sim.gravitational_constant() * 5.97e24;
+    let earth = sim.add_body(SimObj {
+        mass: 5.97e24,
+        ..SimObj::default()
+    });
+    let r = 7.0e6;
+    let ship = sim.add_body(SimObj::ship(
+        Vec3::new(r, 0.0, 0.0),
+        Vec3::new(0.0, (mu / r).sqrt(), 0.0),
+        1000.0,
+    ));
+    assert_eq!(sim.get(ship).unwrap().kind, BodyKind::Ship);
+    sim.set_dt(1.0);
+
+    let length = sim.run_length(600.0);
+    sim.run_for(length);
+    assert_eq!(sim.t(), 600.0);
+
+    // Still on its circle, and the planet hasn't been pulled by it.
+    let p = &sim.get(ship).unwrap().position;
+    assert!(((p.x * p.x + p.y * p.y + p.z * p.z).sqrt() - r).abs() < 1.0);
+    let e = &sim.get(earth).unwrap().position;
+    assert_eq!((e.x, e.y, e.z), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn a_catalog_runs_under_either_integrator() {
+    let catalog = Catalog::parse(&format!(
+        "{}\n\
+         Sun,soleil,,0,0,0,0,0,0,1.989e30,695508.0e3\n\
+         Earth,terre,soleil,1.496e11,0,0,0,29780,0,5.97e24,6371.0e3\n",
+        gravsim::catalog::HEADER
+    ))
+    .unwrap();
+
+    for integrator in [
+        Integrator::Rk4,
+        Integrator::DormandPrince {
+            position_tolerance: 1.0,
+            velocity_tolerance: 1e-6,
+            relative_tolerance: 1e-10,
+        },
+    ] {
+        let (mut sim, ids) = catalog.simulation();
+        assert_eq!(ids.len(), 2);
+        sim.set_integrator(integrator);
+        sim.set_dt(60.0);
+        sim.run_for(3600.0);
+        assert!((sim.t() - 3600.0).abs() < 1e-6);
+        let earth = &sim.get(ids[1]).unwrap().velocity;
+        assert!((earth.y - 29780.0).abs() < 1.0);
+        assert!(earth.x < 0.0);
+    }
+}
@@ -0,0 +1,60 @@
+//! The bus module contains the system bus which can access the memory or memory-mapped peripheral
+//! devices.
+
+use std::sync::{Arc, Mutex};
+
+use crate::devices::Device;
+use crate::exception::Exception;
+
+/// A device mounted on the bus at `base`.
+struct Mount {
+    base: u64,
+    size: u64,
+    device: Arc<Mutex<dyn Device>>,
+}
+
+/// The system bus. Nothing is mapped until the host mounts it; an access that doesn't land
+/// entirely inside one device is an access fault.
+#[derive(Default)]
+pub struct Bus {
+    mounts: Vec<Mount>,
+}
+
+impl Bus {
+    /// Create a new bus object with nothing mounted on it.
+    pub fn new() -> Bus {
+        Self::default()
+    }
+
+    /// Map `device` into the address space from `base`. The device is shared, so the host can
+    /// keep a handle to it and look at it between cycles.
+    pub fn mount<D: Device + 'static>(&mut self, base: u64, device: Arc<Mutex<D>>) {
+        let size = device.lock().unwrap().size();
+        self.mounts.push(Mount { base, size, device });
+    }
+
+    /// The device `size` bits at `addr` fall inside, and the offset of `addr` into it.
+    fn find(&self, addr: u64, size: u8) -> Option<(&Mount, u64)> {
+        let len = u64::from(size / 8);
+        self.mounts.iter().find_map(|mount| {
+            let offset = addr.checked_sub(mount.base)?;
+            (offset.checked_add(len)? <= mount.size).then_some((mount, offset))
+        })
+    }
+
+    /// Load a `size`-bit data from the device that connects to the system bus.
+    pub fn read(&mut self, addr: u64, size: u8) -> Result<u64, Exception> {
+        let (mount, offset) = self.find(addr, size).ok_or(Exception::LoadAccessFault)?;
+        let device = mount.device.lock().unwrap();
+        device.read(offset, size)
+    }
+
+    /// Store a `size`-bit data to the device that connects to the system bus.
+    pub fn write(&mut self, addr: u64, value: u64, size: u8) -> Result<(), Exception> {
+        let (mount, offset) = self
+            .find(addr, size)
+            .ok_or(Exception::StoreAMOAccessFault)?;
+        let mut device = mount.device.lock().unwrap();
+        device.write(offset, value, size)
+    }
+}
@@ -0,0 +1,71 @@
+//! The dram module contains the memory structure and implementation to read/write the memory.
+
+use std::convert::TryFrom;
+
+use crate::cpu::{BYTE, DOUBLEWORD, HALFWORD, WORD};
+use crate::devices::Device;
+use crate::exception::Exception;
+
+/// A block of memory. Addresses are offsets from the start of it, wherever it is mounted.
+#[derive(Debug)]
+pub struct Dram {
+    pub dram: Vec<u8>,
+}
+
+impl Dram {
+    /// Create a new, zeroed memory object of `size` bytes.
+    pub fn new(size: u64) -> Self {
+        Self {
+            dram: vec![0; size as usize],
+        }
+    }
+
+    /// Copy `binary` to the start of the memory, dropping whatever doesn't fit.
+    pub fn initialize(&mut self, binary: Vec<u8>) {
+        let len = binary.len().min(self.dram.len());
+        self.dram[..len].copy_from_slice(&binary[..len]);
+    }
+
+    /// The bytes `size` bits at `offset` cover, if they are all inside the memory.
+    fn range(&self, offset: u64, size: u8) -> Option<std::ops::Range<usize>> {
+        let len = match size {
+            BYTE | HALFWORD | WORD | DOUBLEWORD => usize::from(size / 8),
+            _ => return None,
+        };
+        let start = usize::try_from(offset).ok()?;
+        let end = start.checked_add(len)?;
+        (end <= self.dram.len()).then_some(start..end)
+    }
+
+    /// Load `size`-bit data from the memory with little endian.
+    pub fn read(&self, offset: u64, size: u8) -> Result<u64, Exception> {
+        let range = self.range(offset, size).ok_or(Exception::LoadAccessFault)?;
+        let mut bytes = [0; 8];
+        bytes[..range.len()].copy_from_slice(&self.dram[range]);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Store `size`-bit data to the memory with little endian.
+    pub fn write(&mut self, offset: u64, value: u64, size: u8) -> Result<(), Exception> {
+        let range = self
+            .range(offset, size)
+            .ok_or(Exception::StoreAMOAccessFault)?;
+        let len = range.len();
+        self.dram[range].copy_from_slice(&value.to_le_bytes()[..len]);
+        Ok(())
+    }
+}
+
+impl Device for Dram {
+    fn size(&self) -> u64 {
+        self.dram.len() as u64
+    }
+
+    fn read(&self, offset: u64, size: u8) -> Result<u64, Exception> {
+        Dram::read(self, offset, size)
+    }
+
+    fn write(&mut self, offset: u64, value: u64, size: u8) -> Result<(), Exception> {
+        Dram::write(self, offset, value, size)
+    }
+}
@@ -0,0 +1,16 @@
+//! The devices module contains the peripheral devices that can be mounted on the bus.
+
+pub mod dram;
+
+use crate::exception::Exception;
+
+/// Something that can be mounted on the bus. Offsets are relative to where it is mounted, and
+/// the bus only hands a device accesses that fit inside `size`.
+pub trait Device: Send {
+    /// The number of bytes the device takes up in the address space.
+    fn size(&self) -> u64;
+    /// Load a `size`-bit data from `offset`.
+    fn read(&self, offset: u64, size: u8) -> Result<u64, Exception>;
+    /// Store a `size`-bit data to `offset`.
+    fn write(&mut self, offset: u64, value: u64, size: u8) -> Result<(), Exception>;
+}
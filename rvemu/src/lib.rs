@@ -0,0 +1,26 @@
+//! RISC-V emulator core implementation.
+//!
+//! This is rvemu 0.0.11 cut down to the CPU, trimmed of the QEMU virt
+//! machine, for bubbly_byter to embed: the bus is a table of devices the
+//! host mounts wherever its memory map says, `Cpu::cycle` runs one
+//! instruction and hands every exception back to the host instead of
+//! trapping, and a `JumpLinkHandler` lets the host answer calls to
+//! addresses it provides the functions for.
+
+// Upstream's own style, left alone to keep the diff against it small.
+#![allow(
+    clippy::format_in_format_args,
+    clippy::manual_is_multiple_of,
+    clippy::manual_range_patterns,
+    clippy::needless_bool_assign,
+    clippy::needless_late_init,
+    clippy::needless_return,
+    clippy::new_without_default,
+    clippy::unnecessary_cast
+)]
+
+pub mod bus;
+pub mod cpu;
+pub mod csr;
+pub mod devices;
+pub mod exception;
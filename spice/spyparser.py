@@ -24,16 +24,26 @@ while line := sys.stdin.readline():
                 f = float(part) * 1000
                 values.append(str(f))
 
+        mass = ''
+        radius = ''
+        around = None
+        pid = name.lower()
         for p in meta['bodies']:
             if p['id'].upper() == name.upper() or p['englishName'].upper() == name.upper():
                 if p['mass'] is not None:
-                    values.append(f"{p['mass']['massValue']}e{p['mass']['massExponent']}")
+                    mass = f"{p['mass']['massValue']}e{p['mass']['massExponent']}"
+                if p['meanRadius']:
+                    radius = f"{p['meanRadius']}e3"
+                if p['aroundPlanet'] is not None:
+                    around = p['aroundPlanet']['planet']
+                pid = p['id']
                 break
+        else:
+            # Leave mass and radius empty so loading bodies.csv points at
+            # the row until they're filled in by hand.
+            print(f"no le-systeme-solaire.net body for {name}", file=sys.stderr)
         values = ','.join(values)
-        around = p['aroundPlanet']
-        if around is not None:
-            around = around['planet']
-        n = [name, p['id'], around, f"{values}"]
+        n = [name, pid, around, f"{values},{mass},{radius}"]
         results.append(n)
 
 def planet_index(pid):
@@ -66,11 +76,6 @@ def sort_key(r):
    
 results = sorted(results, key=sort_key)
 
-for (i, r) in enumerate(results):
-    print(f"// {i: 3} {r[0]} ({r[1]})", end='')
-    if r[2] is not None:
-        print(f" (around {r[2]})", end='')
-    print("")
-    print(f"[{r[3]}],")
-
-
+print("name,id,parent,x,y,z,vx,vy,vz,mass,radius")
+for r in results:
+    print(f"{r[0]},{r[1]},{r[2] or ''},{r[3]}")